@@ -0,0 +1,1275 @@
+//! Generates `class!` bindings for Java classes from what `javap` prints about them.  Maven
+//! coordinates stand for every public top-level class in the artifact.
+//!
+//! Types that a stub can't express are erased to `Object`, the way Java erases type variables.
+//! Members that can't be bound at all, like static methods and arrays of anything but a type
+//! parameter, are left out with a comment saying why.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::{self, ClassConfig, Config, Glob, MethodConfig};
+use crate::maven::Coordinates;
+use crate::metadata::{self, Metadata};
+use crate::names::{to_snake_case, KEYWORDS, UNESCAPABLE_KEYWORDS};
+use crate::warn;
+
+pub const USAGE: &str = "cargo graal bindgen [--manifest-path PATH] [--config FILE] [-o FILE] \
+     (CLASS | GLOB | GROUP:ARTIFACT:VERSION)...";
+
+const MODIFIERS: &[&str] = &[
+    "public",
+    "protected",
+    "private",
+    "static",
+    "final",
+    "abstract",
+    "native",
+    "synchronized",
+    "default",
+    "strictfp",
+    "transient",
+    "volatile",
+    "sealed",
+    "non-sealed",
+];
+
+const PRIMITIVES: &[&str] = &[
+    "void", "boolean", "byte", "char", "short", "int", "long", "float", "double",
+];
+
+struct Options {
+    manifest_path: Option<PathBuf>,
+    config: Option<PathBuf>,
+    output: Option<PathBuf>,
+    classes: Vec<String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Options, String> {
+        let mut options = Options {
+            manifest_path: None,
+            config: None,
+            output: None,
+            classes: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .map(PathBuf::from)
+                    .ok_or_else(|| format!("{} needs a path", arg))
+            };
+            match arg.as_str() {
+                "--manifest-path" => options.manifest_path = Some(value()?),
+                "--config" => options.config = Some(value()?),
+                "-o" | "--output" => options.output = Some(value()?),
+                _ if arg.starts_with('-') => {
+                    return Err(format!("unknown option {}, expected {}", arg, USAGE))
+                }
+                _ => options.classes.push(arg.clone()),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// A class as `javap -public` describes it.
+struct Class {
+    /// The fully-qualified name, like `java.util.ArrayList`.
+    name: String,
+    generics: Vec<String>,
+    /// `javap -public` still describes package-private classes, which can't be bound.
+    is_public: bool,
+    is_abstract: bool,
+    /// Whether the class implements or extends `Comparable` itself, or is an enum.  Comparable
+    /// superclasses aren't looked for, since `javap` only prints the direct supertypes.
+    is_comparable: bool,
+    /// The classes and interfaces after `extends` and `implements`, without type arguments.
+    supertypes: Vec<String>,
+    members: Vec<Member>,
+}
+
+struct Member {
+    /// The declaration as `javap` printed it, for comments about it.
+    declaration: String,
+    is_static: bool,
+    name: String,
+    /// The method's own type parameters, like `T` in `<T> T[] toArray(T[])`.
+    type_params: Vec<String>,
+    /// `None` for constructors.
+    return_type: Option<String>,
+    params: Vec<String>,
+}
+
+/// Runs `cargo graal bindgen`.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let options = Options::parse(args)?;
+    let manifest_path = metadata::locate_manifest(options.manifest_path.as_deref())?;
+    let metadata = Metadata::load(&manifest_path)?;
+    let root = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+    let config_path = options
+        .config
+        .unwrap_or_else(|| root.join(config::FILE_NAME));
+    let config = Config::load(&config_path)?;
+
+    let mut includes = config.include.clone();
+    includes.extend(options.classes.iter().cloned());
+    if includes.is_empty() {
+        return Err(format!(
+            "no classes given on the command line or in {}, expected {}",
+            config_path.display(),
+            USAGE
+        ));
+    }
+    let names = class_names(&metadata, &config, &includes)?;
+    if names.is_empty() {
+        return Err("no classes matched".to_owned());
+    }
+    let mut classes = parse(&javap(&metadata, &names)?)?;
+    classes.retain(|class| class.is_public);
+    let bindings = render(&classes, &config, &options.classes)?;
+
+    let output = options.output.or_else(|| {
+        let directory = config_path.parent().unwrap_or(root);
+        config.output.as_ref().map(|output| directory.join(output))
+    });
+    match output {
+        Some(path) => fs::write(&path, bindings)
+            .map_err(|e| format!("couldn't write {}: {}", path.display(), e)),
+        None => {
+            print!("{}", bindings);
+            Ok(())
+        }
+    }
+}
+
+/// The classes that `includes` stand for, in order and without the excluded ones.
+fn class_names(
+    metadata: &Metadata,
+    config: &Config,
+    includes: &[String],
+) -> Result<Vec<String>, String> {
+    let mut classpath = None;
+    let mut names = Vec::new();
+    for include in includes {
+        if include.contains(':') {
+            names.extend(artifact_classes(metadata, &Coordinates::parse(include)?)?);
+        } else if Glob::is_glob(include) {
+            // Only classes on the classpath can be listed, so globs don't match JDK classes.
+            if classpath.is_none() {
+                classpath = Some(classpath_classes(metadata)?);
+            }
+            let glob = Glob::new(include);
+            let classes = classpath.as_ref().unwrap().iter();
+            names.extend(classes.filter(|class| glob.matches(class)).cloned());
+        } else {
+            names.push(include.clone());
+        }
+    }
+    let mut seen = BTreeSet::new();
+    names.retain(|name| !config.excludes(name) && seen.insert(name.clone()));
+    Ok(names)
+}
+
+/// A JDK tool, like `javap`.  GraalVM's is used if `GRAAL_HOME` is set, so the classes are the ones
+/// `lli` will see.
+fn jdk_tool(name: &str) -> PathBuf {
+    env::var_os("GRAAL_HOME")
+        .map(|home| Path::new(&home).join("bin").join(name))
+        .filter(|tool| tool.exists())
+        .unwrap_or_else(|| name.into())
+}
+
+/// The top-level classes in the jar of a Maven artifact in the local repository.
+fn artifact_classes(metadata: &Metadata, coordinates: &Coordinates) -> Result<Vec<String>, String> {
+    let repository = metadata
+        .maven_repository
+        .as_ref()
+        .ok_or("there's no home directory to find ~/.m2/repository in")?;
+    let jar = repository.artifact(coordinates);
+    if !jar.exists() {
+        return Err(format!("{} isn't in the local repository", coordinates));
+    }
+    jar_classes(&jar)
+}
+
+/// The top-level classes in the jars and directories on the classpath.
+fn classpath_classes(metadata: &Metadata) -> Result<Vec<String>, String> {
+    let mut classes = Vec::new();
+    for entry in &metadata.classpath {
+        if entry.is_dir() {
+            directory_classes(entry, entry, &mut classes);
+        } else if entry.exists() {
+            classes.extend(jar_classes(entry)?);
+        }
+    }
+    Ok(classes)
+}
+
+fn jar_classes(jar: &Path) -> Result<Vec<String>, String> {
+    let tool = jdk_tool("jar");
+    let output = Command::new(&tool)
+        .arg("tf")
+        .arg(jar)
+        .output()
+        .map_err(|e| format!("couldn't run {}: {}", tool.display(), e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "couldn't list {}: {}",
+            jar.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(top_level_class)
+        .collect())
+}
+
+fn directory_classes(root: &Path, directory: &Path, classes: &mut Vec<String>) {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+        if path.is_dir() {
+            directory_classes(root, &path, classes);
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let components: Vec<_> = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect();
+            classes.extend(top_level_class(&components.join("/")));
+        }
+    }
+}
+
+/// The class name of a `.class` file in a jar or directory, like `java/util/List.class`, unless
+/// it's a nested class or not a class at all.
+fn top_level_class(path: &str) -> Option<String> {
+    let class = path.strip_suffix(".class")?;
+    let ignored = class.contains('$')
+        || class.ends_with("package-info")
+        || class.ends_with("module-info")
+        || class.starts_with("META-INF/");
+    if ignored {
+        None
+    } else {
+        Some(class.replace('/', "."))
+    }
+}
+
+/// Runs `javap -public` on `classes`, with the crate's classpath.
+fn javap(metadata: &Metadata, classes: &[String]) -> Result<String, String> {
+    let javap = jdk_tool("javap");
+    let mut command = Command::new(&javap);
+    command.arg("-public");
+    if let Some(classpath) = metadata.classpath()? {
+        command.arg("-cp").arg(classpath);
+    }
+    let output = command
+        .args(classes)
+        .output()
+        .map_err(|e| format!("couldn't run {}: {}", javap.display(), e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "javap failed: {}{}",
+            String::from_utf8_lossy(&output.stdout).trim(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn parse(javap: &str) -> Result<Vec<Class>, String> {
+    let mut classes: Vec<Class> = Vec::new();
+    for line in javap.lines().map(str::trim) {
+        if line.ends_with('{') {
+            classes.push(parse_class(line)?);
+        } else if line.ends_with(';') && line.contains('(') {
+            let class = classes
+                .last_mut()
+                .ok_or_else(|| format!("member outside a class: {}", line))?;
+            let member = parse_member(line, &class.name)?;
+            class.members.push(member);
+        }
+    }
+    Ok(classes)
+}
+
+/// `public class java.util.ArrayList<E> extends ... {`
+fn parse_class(line: &str) -> Result<Class, String> {
+    let tokens = split_top_level(line.trim_end_matches('{'), ' ');
+    let kind = tokens
+        .iter()
+        .position(|token| *token == "class" || *token == "interface")
+        .ok_or_else(|| format!("unrecognized class declaration: {}", line))?;
+    let name = tokens
+        .get(kind + 1)
+        .ok_or_else(|| format!("unrecognized class declaration: {}", line))?;
+    let (name, generics) = split_generics(name);
+    let supertypes: Vec<String> = tokens[kind + 2..]
+        .iter()
+        .filter(|token| **token != "extends" && **token != "implements")
+        .map(|token| split_generics(token.trim_end_matches(',')).0.to_owned())
+        .collect();
+    let is_comparable = supertypes
+        .iter()
+        .any(|supertype| supertype == "java.lang.Comparable" || supertype == "java.lang.Enum");
+    Ok(Class {
+        name: name.to_owned(),
+        generics: generics.map(type_param_names).unwrap_or_default(),
+        is_public: tokens[..kind].contains(&"public"),
+        is_abstract: tokens[..kind].contains(&"abstract") || tokens[kind] == "interface",
+        is_comparable,
+        supertypes,
+        members: Vec::new(),
+    })
+}
+
+/// `public <T> T[] toArray(T[]) throws ...;` or `public java.util.ArrayList(int);`
+fn parse_member(line: &str, class: &str) -> Result<Member, String> {
+    let declaration = line.trim_end_matches(';');
+    let open = declaration.find('(').unwrap();
+    let close = declaration
+        .rfind(')')
+        .ok_or_else(|| format!("unrecognized member: {}", line))?;
+    let mut tokens = split_top_level(&declaration[..open], ' ');
+    let is_static = tokens.contains(&"static");
+    tokens.retain(|token| !MODIFIERS.contains(token));
+
+    let type_params = match tokens.first() {
+        Some(token) if token.starts_with('<') => {
+            let params = type_param_names(&token[1..token.len() - 1]);
+            tokens.remove(0);
+            params
+        }
+        _ => Vec::new(),
+    };
+    let name = tokens
+        .pop()
+        .ok_or_else(|| format!("unrecognized member: {}", line))?;
+    let is_constructor = name == class;
+    Ok(Member {
+        declaration: declaration.to_owned(),
+        is_static,
+        name: if is_constructor {
+            String::new()
+        } else {
+            name.to_owned()
+        },
+        type_params,
+        return_type: tokens.pop().filter(|_| !is_constructor).map(str::to_owned),
+        params: split_top_level(&declaration[open + 1..close], ',')
+            .into_iter()
+            .map(str::to_owned)
+            .collect(),
+    })
+}
+
+/// Splits `s` at `separator`s that aren't inside angle brackets, dropping empty parts.
+pub fn split_top_level(s: &str, separator: char) -> Vec<&str> {
+    let (mut parts, mut depth, mut start) = (Vec::new(), 0, 0);
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            _ if c == separator && depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(s[start..].trim());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// `java.util.List<E>` → (`java.util.List`, `Some("E")`)
+fn split_generics(ty: &str) -> (&str, Option<&str>) {
+    match ty.find('<') {
+        Some(open) if ty.ends_with('>') => (&ty[..open], Some(&ty[open + 1..ty.len() - 1])),
+        _ => (ty, None),
+    }
+}
+
+/// `K extends java.lang.Comparable<? super K>, V` → `[K, V]`
+fn type_param_names(params: &str) -> Vec<String> {
+    split_top_level(params, ',')
+        .into_iter()
+        .filter_map(|param| param.split_whitespace().next())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// The last part of a fully-qualified name, which `class!` names the struct after.
+fn simple_name(name: &str) -> &str {
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+/// A class to generate, with its settings and the module it goes in.
+struct Generated<'a> {
+    class: &'a Class,
+    config: ClassConfig,
+    module: Vec<String>,
+}
+
+/// What the bindings in one module can refer to.
+struct Scope<'a> {
+    /// The module the bindings are in.
+    module: &'a [String],
+    /// Generated classes, with their type parameters and modules.
+    generated: BTreeMap<&'a str, (&'a [String], &'a [String])>,
+    /// The direct supertypes of every class `javap` described.
+    supertypes: BTreeMap<&'a str, &'a [String]>,
+    /// The builtins the bindings can use instead of generated classes.
+    builtins: BTreeMap<&'static str, &'static str>,
+    /// The builtins the bindings use, which have to be imported.
+    used_builtins: RefCell<BTreeSet<&'static str>>,
+}
+
+impl<'a> Scope<'a> {
+    fn new(generated: &'a [Generated], module: &'a [String]) -> Scope<'a> {
+        Scope {
+            module,
+            generated: generated
+                .iter()
+                .filter(|generated| !generated.class.name.contains('$'))
+                .map(|generated| {
+                    let class = generated.class;
+                    (
+                        class.name.as_str(),
+                        (class.generics.as_slice(), generated.module.as_slice()),
+                    )
+                })
+                .collect(),
+            supertypes: generated
+                .iter()
+                .map(|generated| {
+                    let class = generated.class;
+                    (class.name.as_str(), class.supertypes.as_slice())
+                })
+                .collect(),
+            builtins: [
+                ("java.lang.Object", "Object"),
+                ("java.lang.String", "String"),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+            used_builtins: RefCell::default(),
+        }
+    }
+
+    /// Whether the return type `a` of a method of `class` is more specific than `b`, like an
+    /// overriding method's return type is than its bridge method's.  Only the supertypes of
+    /// classes `javap` described are known, but a bridge method's return type is always a
+    /// supertype of the method's, so `class` itself is more specific than anything, and anything
+    /// is more specific than `Object`.
+    fn is_more_specific(&self, class: &str, a: &str, b: &str) -> bool {
+        let (a, _) = split_generics(a);
+        let (b, _) = split_generics(b);
+        if a == b || PRIMITIVES.contains(&a) {
+            return false;
+        }
+        if a == class || b == "java.lang.Object" {
+            return true;
+        }
+        let mut unvisited = vec![a];
+        let mut visited = BTreeSet::new();
+        while let Some(ty) = unvisited.pop() {
+            if !visited.insert(ty) {
+                continue;
+            }
+            for supertype in self.supertypes.get(ty).copied().unwrap_or_default() {
+                if supertype == b {
+                    return true;
+                }
+                unvisited.push(supertype.as_str());
+            }
+        }
+        false
+    }
+
+    /// The name of a builtin, or the path of the generated class that replaces it.
+    fn builtin(&self, java_name: &str) -> Option<String> {
+        let name = *self.builtins.get(java_name)?;
+        match self.generated.get(java_name) {
+            Some((_, module)) => Some(self.path(module, name)),
+            None => {
+                self.used_builtins.borrow_mut().insert(name);
+                Some(name.to_owned())
+            }
+        }
+    }
+
+    /// The path of a generated class in `module`, relative to this one.
+    fn path(&self, module: &[String], name: &str) -> String {
+        let common = self
+            .module
+            .iter()
+            .zip(module)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let mut path = vec!["super"; self.module.len() - common];
+        path.extend(module[common..].iter().map(String::as_str));
+        path.push(name);
+        path.join("::")
+    }
+
+    /// The stub type for a Java type in a member of `class`, or `None` if it can't be expressed.
+    fn stub_type(&self, ty: &str, class: &Class, member: &Member) -> Option<String> {
+        if let Some(element) = ty.strip_suffix("[]").or_else(|| ty.strip_suffix("...")) {
+            // `JavaArray`s need the element's `Passable` type, which only type parameters have.
+            return if class.generics.iter().any(|param| param == element) {
+                Some(format!("{}[]", element))
+            } else {
+                None
+            };
+        }
+        if ty == "char" {
+            // `jtypes` names it `jchar`, so it doesn't shadow Rust's `char`.
+            return Some("jchar".to_owned());
+        }
+        if PRIMITIVES.contains(&ty) || class.generics.iter().any(|param| param == ty) {
+            return Some(ty.to_owned());
+        }
+        self.reference_type(ty, class, member)
+            .or_else(|| self.builtin("java.lang.Object"))
+    }
+
+    /// The stub type for a class type, or `None` if it has to be erased to `Object`.
+    fn reference_type(&self, ty: &str, class: &Class, member: &Member) -> Option<String> {
+        let (name, args) = split_generics(ty);
+        if member.type_params.iter().any(|param| param == name) {
+            return None;
+        }
+        let (params, module) = match self.generated.get(name) {
+            Some(generated) => generated,
+            None if args.is_none() => return self.builtin(name),
+            None => return None,
+        };
+        let args = args
+            .map(|args| split_top_level(args, ','))
+            .unwrap_or_default();
+        if args.len() != params.len() {
+            return None;
+        }
+
+        // `class!` adds a `Passable` parameter for each type parameter, after all of them.
+        let mut types = Vec::new();
+        let mut passables = Vec::new();
+        for arg in args {
+            if class.generics.iter().any(|param| param == arg) {
+                types.push(arg.to_owned());
+                passables.push(format!("{}Passable", arg));
+            } else {
+                types.push(self.reference_type(arg, class, member)?);
+                passables.push("*mut Value".to_owned());
+            }
+        }
+        types.extend(passables);
+        let path = self.path(module, simple_name(name));
+        Some(if types.is_empty() {
+            path
+        } else {
+            format!("{}<{}>", path, types.join(", "))
+        })
+    }
+}
+
+/// Renders `classes` as `class!` invocations in the modules `config` puts them in.
+fn render(classes: &[Class], config: &Config, requested: &[String]) -> Result<String, String> {
+    let mut generated = Vec::new();
+    for class in classes {
+        let config = config.class(&class.name);
+        let mut module: Vec<String> = match &config.module {
+            Some(module) => module.split("::").map(str::to_owned).collect(),
+            None => Vec::new(),
+        };
+        if let Some(segment) = module.iter().find(|segment| !is_identifier(segment)) {
+            return Err(format!(
+                "{}: module {} has an invalid segment `{}`",
+                class.name,
+                config.module.as_deref().unwrap_or_default(),
+                segment
+            ));
+        }
+        if config.package_modules == Some(true) {
+            if let Some((package, _)) = class.name.rsplit_once('.') {
+                for segment in package.split('.') {
+                    module.push(package_module(segment).ok_or_else(|| {
+                        format!(
+                            "{}: the package `{}` can't be a module name",
+                            class.name, segment
+                        )
+                    })?);
+                }
+            }
+        }
+        generated.push(Generated {
+            class,
+            config,
+            module,
+        });
+    }
+
+    let mut command = String::from("cargo graal bindgen");
+    for arg in requested {
+        command.push(' ');
+        command.push_str(arg);
+    }
+    Ok(format!(
+        "// Generated by `{}`.\n\n{}",
+        command,
+        render_module(&generated, &[])?
+    ))
+}
+
+/// The module for a segment of a package name, which is a raw identifier if it's a keyword, like
+/// `r#impl` for `com.example.impl`.
+fn package_module(segment: &str) -> Option<String> {
+    if is_identifier(segment) {
+        Some(segment.to_owned())
+    } else if KEYWORDS.contains(&segment) {
+        Some(format!("r#{}", segment))
+    } else {
+        None
+    }
+}
+
+/// Renders the classes in `module`, and its submodules.
+fn render_module(generated: &[Generated], module: &[String]) -> Result<String, String> {
+    let scope = Scope::new(generated, module);
+    let mut body = String::new();
+    let mut submodules = BTreeSet::new();
+    // `class!` names the struct after the class without its package.
+    let mut names = BTreeMap::new();
+    for generated in generated {
+        if generated.module == module {
+            let name = &generated.class.name;
+            if let Some(other) = names.insert(simple_name(name), name) {
+                return Err(format!(
+                    "{} and {} would both be {} in {};  set package-modules = true, or give \
+                     one of them another module",
+                    other,
+                    name,
+                    simple_name(name),
+                    if module.is_empty() {
+                        "the same module".to_owned()
+                    } else {
+                        module.join("::")
+                    }
+                ));
+            }
+            body.push('\n');
+            body.push_str(&render_class(generated, &scope)?);
+        } else if generated.module.starts_with(module) {
+            submodules.insert(&generated.module[module.len()]);
+        }
+    }
+
+    let mut out = String::new();
+    if !body.is_empty() {
+        // Only generic classes have `PhantomData` fields.
+        let generic = generated
+            .iter()
+            .any(|generated| generated.module == module && !generated.class.generics.is_empty());
+        if generic {
+            out.push_str("use std::marker::PhantomData;\n\n");
+        }
+        let builtins = scope.used_builtins.into_inner();
+        if !builtins.is_empty() {
+            let builtins: Vec<&str> = builtins.into_iter().collect();
+            match builtins.as_slice() {
+                [builtin] => out.push_str(&format!("use crate::builtins::{};\n", builtin)),
+                _ => out.push_str(&format!(
+                    "use crate::builtins::{{{}}};\n",
+                    builtins.join(", ")
+                )),
+            }
+        }
+        // Constructors call `from_polyglot_value`.
+        if body.contains("\n    new(") || body.contains("\n    new_") {
+            out.push_str("use crate::polyglot::{Receive, Value};\n");
+        } else {
+            out.push_str("use crate::polyglot::Value;\n");
+        }
+        out.push_str("use crate::types::jtypes::*;\n");
+        out.push_str("use graal_bindgen_macros::class;\n");
+        out.push_str(&body);
+    }
+
+    for submodule in submodules {
+        let mut path = module.to_vec();
+        path.push(submodule.clone());
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("pub mod {} {{\n", submodule));
+        for line in render_module(generated, &path)?.lines() {
+            if !line.is_empty() {
+                out.push_str("    ");
+                out.push_str(line);
+            }
+            out.push('\n');
+        }
+        out.push_str("}\n");
+    }
+    Ok(out)
+}
+
+/// The name a member is configured by:  `new` for constructors.
+fn java_name(member: &Member) -> &str {
+    if member.return_type.is_none() {
+        "new"
+    } else {
+        &member.name
+    }
+}
+
+fn render_class(generated: &Generated, scope: &Scope) -> Result<String, String> {
+    let class = generated.class;
+    if class.name.contains('$') {
+        return Ok(format!(
+            "// Skipped `{}`:  nested classes can't be bound yet.\n",
+            class.name
+        ));
+    }
+
+    // Bridge methods have the same parameters as the method they're for, and a less specific
+    // return type, so only the method with the most specific return type is kept.
+    let mut members: Vec<&Member> = Vec::new();
+    let mut signatures = BTreeMap::new();
+    for member in &class.members {
+        match signatures.get(&(java_name(member), &member.params)) {
+            Some(&i) => {
+                let kept: &mut &Member = &mut members[i];
+                if let (Some(a), Some(b)) = (&member.return_type, &kept.return_type) {
+                    if scope.is_more_specific(&class.name, a, b) {
+                        *kept = member;
+                    }
+                }
+            }
+            None => {
+                signatures.insert((java_name(member), &member.params), members.len());
+                members.push(member);
+            }
+        }
+    }
+
+    let mut stubs = Vec::new();
+    for member in members {
+        let java_name = java_name(member);
+        if generated.config.includes_method(java_name) {
+            let config = generated.config.method(java_name, &member.params);
+            let snake_case = generated.config.snake_case == Some(true);
+            stubs.push((member, Stub::new(class, member, &config, snake_case, scope)));
+        }
+    }
+
+    // Renamed methods get their names first, so the rest can avoid them.  Each name maps to what
+    // it's taken by.
+    let mut taken: BTreeMap<String, &str> = BTreeMap::new();
+    for keyword in UNESCAPABLE_KEYWORDS {
+        taken.insert(keyword.to_string(), "Rust");
+    }
+    taken.insert("clone_ref".to_owned(), "class!");
+    for (_, stub) in &stubs {
+        if let Ok(Stub {
+            rename: Some(rename),
+            java_name,
+            ..
+        }) = stub
+        {
+            if taken.insert(rename.clone(), java_name).is_some() {
+                return Err(format!(
+                    "{}: more than one method is called {} in Rust",
+                    class.name, rename
+                ));
+            }
+        }
+    }
+
+    // Overloads are named after their parameter types, like `class!` names them.
+    let mut seen = BTreeSet::new();
+    let mut overloaded = BTreeSet::new();
+    for (_, stub) in &stubs {
+        if let Ok(stub) = stub {
+            if stub.rename.is_none() && !seen.insert(stub.java_name) {
+                overloaded.insert(stub.java_name);
+            }
+        }
+    }
+
+    let mut out = String::from("class! [");
+    if generated.config.snake_case == Some(true) {
+        out.push_str("#[snake_case] ");
+    }
+    out.push_str(&class.name);
+    if !class.generics.is_empty() {
+        out.push_str(&format!("<{}>", class.generics.join(", ")));
+    }
+    if class.is_comparable {
+        out.push_str(" implements Comparable");
+    }
+    out.push_str(" {\n");
+    for (member, stub) in &stubs {
+        match stub {
+            Ok(stub) => {
+                let rust_name = match &stub.rename {
+                    Some(rename) => rename.clone(),
+                    // Names that are still taken get numbered.
+                    None => {
+                        let mut base = stub.default_name.clone();
+                        if overloaded.contains(stub.java_name) {
+                            for param in &member.params {
+                                base.push('_');
+                                base.push_str(&name_suffix(param));
+                            }
+                        }
+                        if let Some(other) = taken.get(&base) {
+                            let reason = if *other == stub.java_name {
+                                "another overload".to_owned()
+                            } else {
+                                other.to_string()
+                            };
+                            return Err(format!(
+                                "{}.{}: {} is taken by {};  add a `rename` for \"{}\" under \
+                                 [classes.\"{}\".methods] in graal-bindgen.toml",
+                                class.name,
+                                stub.java_name,
+                                base,
+                                reason,
+                                config::method_key(stub.java_name, &member.params),
+                                class.name
+                            ));
+                        }
+                        taken.insert(base.clone(), stub.java_name);
+                        base
+                    }
+                };
+                out.push_str(&format!("    {};\n", stub.render(&rust_name)));
+            }
+            Err(reason) => out.push_str(&format!(
+                "    // Skipped `{}`:  {}.\n",
+                member.declaration, reason
+            )),
+        }
+    }
+    out.push_str("}];\n");
+    Ok(out)
+}
+
+/// Whether `name` can be a Rust identifier without being raw.
+pub fn is_identifier(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !KEYWORDS.contains(&name)
+        && !UNESCAPABLE_KEYWORDS.contains(&name)
+}
+
+/// A constructor or method stub, before it's given a Rust name.
+struct Stub<'a> {
+    mutating: bool,
+    /// `None` for constructors.
+    return_type: Option<String>,
+    /// `new` for constructors.
+    java_name: &'a str,
+    /// The name `class!` gives the method if the stub doesn't give one.
+    default_name: String,
+    rename: Option<String>,
+    params: Vec<String>,
+}
+
+impl<'a> Stub<'a> {
+    /// The stub for `member`, or why it can't be bound.
+    fn new(
+        class: &Class,
+        member: &'a Member,
+        config: &MethodConfig,
+        snake_case: bool,
+        scope: &Scope,
+    ) -> Result<Stub<'a>, &'static str> {
+        if member.is_static {
+            return Err("static methods can't be bound yet");
+        }
+        let java_name = if member.return_type.is_none() {
+            "new"
+        } else {
+            member.name.as_str()
+        };
+        let warn_about = |problem: String| {
+            warn(format!("{}.{}: {}", class.name, java_name, problem));
+        };
+
+        let mut params = Vec::new();
+        for (i, param) in member.params.iter().enumerate() {
+            let mut ty = scope
+                .stub_type(param, class, member)
+                .ok_or("arrays of this type can't be bound")?;
+            if config.nullable_params.contains(&i) {
+                // `Option` only passes types that are passed as polyglot values.
+                if is_reference(param) && !class.generics.iter().any(|generic| generic == param) {
+                    ty = format!("Option<{}>", ty);
+                } else {
+                    warn_about(format!("parameter {} ({}) can't be nullable", i, param));
+                }
+            }
+            params.push(format!("{} arg{}", ty, i));
+        }
+        if let Some(&i) = config
+            .nullable_params
+            .iter()
+            .find(|&&i| i >= member.params.len())
+        {
+            warn_about(format!("there's no parameter {}", i));
+        }
+
+        let return_type = match &member.return_type {
+            None if class.is_abstract => return Err("the class is abstract"),
+            None => None,
+            Some(return_type) => {
+                if java_name.contains('$') {
+                    return Err("the name isn't a valid method name");
+                }
+                let ty = scope
+                    .stub_type(return_type, class, member)
+                    .ok_or("arrays of this type can't be bound")?;
+                Some(
+                    if config.nullable == Some(true) && is_reference(return_type) {
+                        format!("Option<{}>", ty)
+                    } else {
+                        ty
+                    },
+                )
+            }
+        };
+        if config.mutating.is_some() && return_type.is_none() {
+            warn_about("constructors can't be mut".to_owned());
+        }
+        let default_name = if snake_case && return_type.is_some() {
+            to_snake_case(java_name)
+        } else {
+            java_name.to_owned()
+        };
+        Ok(Stub {
+            mutating: config.mutating == Some(true) && return_type.is_some(),
+            return_type,
+            java_name,
+            default_name,
+            rename: config.rename.clone(),
+            params,
+        })
+    }
+
+    fn render(&self, rust_name: &str) -> String {
+        let params = self.params.join(", ");
+        let prefix = if self.mutating { "mut " } else { "" };
+        match &self.return_type {
+            None => format!("{}({})", rust_name, params),
+            // `class!` derives the Rust name from the Java name, escaping keywords itself.
+            Some(return_type) if rust_name == self.default_name => {
+                format!("{}{} {}({})", prefix, return_type, self.java_name, params)
+            }
+            Some(return_type) => format!(
+                "{}{} {} {}({})",
+                prefix, return_type, rust_name, self.java_name, params
+            ),
+        }
+    }
+}
+
+/// A Java type in snake case, without its package or type arguments, the way `class!` names
+/// overloads:  `java.util.Collection<? extends E>` is `collection`, and `E[]` is `e_array`.
+fn name_suffix(ty: &str) -> String {
+    if let Some(element) = ty.strip_suffix("[]").or_else(|| ty.strip_suffix("...")) {
+        return name_suffix(element) + "_array";
+    }
+    let (name, _) = split_generics(ty);
+    let name = name.rsplit(['.', '$']).next().unwrap_or(name);
+    to_snake_case(name).to_lowercase()
+}
+
+/// Whether a Java type is an object, rather than a primitive or an array, which `Option` can't
+/// wrap in a stub.
+fn is_reference(ty: &str) -> bool {
+    !PRIMITIVES.contains(&ty) && !ty.ends_with("[]") && !ty.ends_with("...")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_javap(javap: &str) -> Result<String, String> {
+        render(&parse(javap)?, &Config::default(), &[])
+    }
+
+    fn render_with_config(javap: &str, config: &str) -> Result<String, String> {
+        let config = Config::from_toml(&config.parse().unwrap())?;
+        render(&parse(javap)?, &config, &[])
+    }
+
+    #[test]
+    fn binds_the_method_a_bridge_is_for() {
+        let javap = "\
+public final class java.lang.StringBuilder extends java.lang.AbstractStringBuilder implements java.lang.CharSequence {
+  public java.lang.AbstractStringBuilder append(int);
+  public java.lang.Appendable append(char) throws java.io.IOException;
+  public java.lang.StringBuilder append(char);
+  public java.lang.StringBuilder append(int);
+}
+public abstract class java.util.AbstractList<E> extends java.util.AbstractCollection<E> implements java.util.List<E> {
+  public abstract E get(int);
+}
+public class java.util.ArrayList<E> extends java.util.AbstractList<E> {
+  public java.lang.Object get(int);
+  public E get(int);
+  public java.util.AbstractList<E> subList(int, int);
+  public java.util.ArrayList<E> subList(int, int);
+}
+";
+        let bindings = render_javap(javap).unwrap();
+        assert!(bindings.contains("StringBuilder append_int append(int arg0);"));
+        assert!(bindings.contains("StringBuilder append_char append(jchar arg0);"));
+        assert!(bindings.contains("    E get(int arg0);"));
+        assert!(bindings.contains("    ArrayList<E, EPassable> subList(int arg0, int arg1);"));
+        assert!(!bindings.contains("AbstractStringBuilder"));
+    }
+
+    #[test]
+    fn ambiguous_overloads_ask_for_a_rename() {
+        let javap = "\
+public class com.example.Lists {
+  public void foo(com.example.a.List);
+  public void foo(com.example.b.List);
+}
+";
+        let error = render_javap(javap).unwrap_err();
+        assert_eq!(
+            error,
+            "com.example.Lists.foo: foo_list is taken by another overload;  add a `rename` for \
+             \"foo(List)\" under [classes.\"com.example.Lists\".methods] in graal-bindgen.toml"
+        );
+    }
+
+    #[test]
+    fn converts_names_to_snake_case() {
+        assert_eq!(to_snake_case("size"), "size");
+        assert_eq!(to_snake_case("toArray"), "to_array");
+        assert_eq!(to_snake_case("getURLPath"), "get_url_path");
+        assert_eq!(to_snake_case("URL"), "url");
+        assert_eq!(to_snake_case("HTMLParser"), "html_parser");
+        assert_eq!(to_snake_case("get_Value"), "get_value");
+        assert_eq!(to_snake_case("utf8Bytes"), "utf8_bytes");
+    }
+
+    #[test]
+    fn snake_case_leaves_keywords_to_class() {
+        let javap = "\
+public class com.example.Node {
+  public java.lang.String type();
+  public int childCount();
+  public com.example.Node self();
+}
+";
+        let error = render_with_config(javap, "snake-case = true").unwrap_err();
+        assert!(
+            error.starts_with("com.example.Node.self: self is taken by Rust;  "),
+            "{}",
+            error
+        );
+
+        let config = r#"
+            snake-case = true
+            [classes."com.example.Node".methods]
+            "self" = { rename = "this" }
+        "#;
+        let bindings = render_with_config(javap, config).unwrap();
+        assert!(bindings.contains("class! [#[snake_case] com.example.Node {"));
+        assert!(bindings.contains("    String type();"));
+        assert!(bindings.contains("    int childCount();"));
+        assert!(bindings.contains("    Node this self();"));
+        assert!(is_identifier("child_count"));
+        assert!(!is_identifier("type"));
+        assert!(!is_identifier("gen"));
+        assert!(!is_identifier("self"));
+        assert!(!is_identifier("2d"));
+    }
+
+    #[test]
+    fn names_overloads_after_their_parameter_types() {
+        assert_eq!(name_suffix("int"), "int");
+        assert_eq!(
+            name_suffix("java.util.Collection<? extends E>"),
+            "collection"
+        );
+        assert_eq!(name_suffix("java.util.Map$Entry<K, V>"), "entry");
+        assert_eq!(name_suffix("java.lang.CharSequence"), "char_sequence");
+        assert_eq!(name_suffix("E[]"), "e_array");
+        assert_eq!(name_suffix("java.lang.Object..."), "object_array");
+        assert_eq!(name_suffix("java.net.URL"), "url");
+
+        let javap = "\
+public class java.util.ArrayList<E> {
+  public java.util.ArrayList();
+  public java.util.ArrayList(int);
+  public boolean add(E);
+  public void add(int, E);
+  public E remove(int);
+  public boolean remove(java.lang.Object);
+  public void clear();
+}
+";
+        let config = r#"
+            [classes."java.util.ArrayList".methods]
+            "add(int, E)" = { rename = "add_at" }
+        "#;
+        let bindings = render_with_config(javap, config).unwrap();
+        let stubs: Vec<&str> = bindings
+            .lines()
+            .skip_while(|line| !line.starts_with("class!"))
+            .collect();
+        assert_eq!(
+            stubs,
+            [
+                "class! [java.util.ArrayList<E> {",
+                "    new();",
+                "    new_int(int arg0);",
+                "    boolean add(E arg0);",
+                "    void add_at add(int arg0, E arg1);",
+                "    E remove_int remove(int arg0);",
+                "    boolean remove_object remove(Object arg0);",
+                "    void clear();",
+                "}];",
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_javap_declarations() {
+        let class = parse_class(
+            "public abstract class java.util.AbstractMap<K, V extends java.lang.Comparable<? super V>> \
+             extends java.lang.Object implements java.util.Map<K, V> {",
+        )
+        .unwrap();
+        assert_eq!(class.name, "java.util.AbstractMap");
+        assert_eq!(class.generics, ["K", "V"]);
+        assert!(class.is_public && class.is_abstract && !class.is_comparable);
+        assert_eq!(class.supertypes, ["java.lang.Object", "java.util.Map"]);
+
+        let class = parse_class("final class java.lang.StringCoding {").unwrap();
+        assert!(!class.is_public && !class.is_abstract);
+        let class = parse_class("public interface java.lang.Runnable {").unwrap();
+        assert!(class.is_abstract);
+        let class =
+            parse_class("public final class java.lang.Thread$State extends java.lang.Enum<java.lang.Thread$State> {")
+                .unwrap();
+        assert!(class.is_comparable);
+        assert!(parse_class("static {};").is_err());
+
+        let member = parse_member(
+            "public <T> T[] toArray(T[]) throws java.lang.ArrayStoreException;",
+            "java.util.ArrayList",
+        )
+        .unwrap();
+        assert_eq!(member.name, "toArray");
+        assert_eq!(member.type_params, ["T"]);
+        assert_eq!(member.return_type.as_deref(), Some("T[]"));
+        assert_eq!(member.params, ["T[]"]);
+        assert!(!member.is_static);
+
+        let member = parse_member(
+            "public java.util.HashMap(java.util.Map<? extends K, ? extends V>);",
+            "java.util.HashMap",
+        )
+        .unwrap();
+        assert_eq!(member.name, "");
+        assert_eq!(member.return_type, None);
+        assert_eq!(member.params, ["java.util.Map<? extends K, ? extends V>"]);
+
+        let member = parse_member(
+            "public static synchronized native long nanoTime();",
+            "java.lang.System",
+        )
+        .unwrap();
+        assert!(member.is_static);
+        assert_eq!(member.return_type.as_deref(), Some("long"));
+        assert!(member.params.is_empty());
+    }
+
+    #[test]
+    fn refers_to_classes_by_relative_paths() {
+        let module =
+            |path: &[&str]| -> Vec<String> { path.iter().map(|s| s.to_string()).collect() };
+        let here = module(&["java", "util"]);
+        let scope = Scope::new(&[], &here);
+        assert_eq!(scope.path(&module(&["java", "util"]), "List"), "List");
+        assert_eq!(
+            scope.path(&module(&["java", "lang"]), "String"),
+            "super::lang::String"
+        );
+        assert_eq!(scope.path(&module(&[]), "Top"), "super::super::Top");
+        assert_eq!(
+            scope.path(&module(&["java", "util", "concurrent"]), "Future"),
+            "concurrent::Future"
+        );
+        let root = module(&[]);
+        let scope = Scope::new(&[], &root);
+        assert_eq!(
+            scope.path(&module(&["java", "sql"]), "Date"),
+            "java::sql::Date"
+        );
+    }
+
+    #[test]
+    fn escapes_package_segments() {
+        assert_eq!(package_module("util").as_deref(), Some("util"));
+        assert_eq!(package_module("impl").as_deref(), Some("r#impl"));
+        assert_eq!(package_module("self"), None);
+        assert_eq!(package_module("gen").as_deref(), Some("r#gen"));
+        assert_eq!(package_module("2d"), None);
+    }
+
+    #[test]
+    fn puts_classes_in_package_modules() {
+        let javap = "\
+public class java.util.Date {
+  public java.sql.Date toSql();
+}
+public class java.sql.Date {
+  public java.util.Date toUtil();
+  public java.lang.String toString();
+}
+";
+        let bindings = render_with_config(javap, "package-modules = true").unwrap();
+        assert!(bindings.contains("pub mod java {\n    pub mod sql {\n"));
+        assert!(bindings.contains("        use crate::builtins::String;\n"));
+        assert!(bindings.contains("        class! [java.sql.Date {"));
+        assert!(bindings.contains("            super::util::Date toUtil();"));
+        assert!(bindings.contains("            super::sql::Date toSql();"));
+
+        let error = render_with_config(
+            "public class com.example.self.Foo {\n}\n",
+            "package-modules = true",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            "com.example.self.Foo: the package `self` can't be a module name"
+        );
+        let error = render_with_config(javap, "module = \"a::2b\"").unwrap_err();
+        assert_eq!(
+            error,
+            "java.util.Date: module a::2b has an invalid segment `2b`"
+        );
+    }
+}
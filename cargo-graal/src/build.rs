@@ -0,0 +1,164 @@
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde_json::Value;
+
+use crate::metadata;
+
+/// GraalVM's LLVM toolchain, found through `GRAAL_HOME`.
+pub struct Toolchain {
+    pub graal_home: PathBuf,
+}
+
+impl Toolchain {
+    pub fn from_env() -> Result<Toolchain, String> {
+        let graal_home = env::var_os("GRAAL_HOME").map(PathBuf::from).ok_or(
+            "environment variable GRAAL_HOME is not set;  set it to GraalVM's root directory",
+        )?;
+        let toolchain = Toolchain { graal_home };
+        if !toolchain.native_bin().exists() {
+            return Err(format!(
+                "LLVM toolchain was not found in {};  try running \"gu install llvm-toolchain\"",
+                toolchain.native_bin().display()
+            ));
+        }
+        Ok(toolchain)
+    }
+
+    fn native_bin(&self) -> PathBuf {
+        self.graal_home.join("languages/llvm/native/bin")
+    }
+
+    pub fn lli(&self) -> PathBuf {
+        self.graal_home.join("languages/llvm/bin/lli")
+    }
+
+    /// Links with the toolchain's `clang` and emits bitcode alongside the usual output, which is
+    /// what `lli` runs.  `-Og` is `-O -g`, optimized with debug info, as `Makefile.toml` built it.
+    /// It also passed `-C ar`, which is left out since rustc ignores it with a deprecation warning.
+    /// Any `RUSTFLAGS` already set are kept.
+    fn rustflags(&self) -> String {
+        let bin = self.native_bin();
+        let mut flags = format!(
+            "-C linker={} -C link-args=-lgraalvm-llvm -Og --emit=llvm-bc",
+            bin.join("clang").display()
+        );
+        if let Ok(extra) = env::var("RUSTFLAGS") {
+            if !extra.trim().is_empty() {
+                flags.push(' ');
+                flags.push_str(&extra);
+            }
+        }
+        flags
+    }
+}
+
+/// A binary that cargo built, and the bitcode `lli` runs for it.
+pub struct Artifact {
+    pub name: String,
+    /// The `Cargo.toml` of the package the binary is in.
+    pub manifest_path: PathBuf,
+    pub bitcode: PathBuf,
+}
+
+/// Runs `cargo build` with `args`, and returns the bitcode of every binary it built.
+pub fn build(toolchain: &Toolchain, args: &[String]) -> Result<Vec<Artifact>, String> {
+    let mut child = Command::new(metadata::cargo())
+        .arg("build")
+        .arg("--message-format=json-render-diagnostics")
+        .args(args)
+        .env("RUSTFLAGS", toolchain.rustflags())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("couldn't run cargo: {}", e))?;
+
+    let mut artifacts = Vec::new();
+    for line in BufReader::new(child.stdout.take().unwrap()).lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        let message: Value = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(_) => {
+                println!("{}", line);
+                continue;
+            }
+        };
+        if message["reason"] != "compiler-artifact" {
+            continue;
+        }
+        if let Some(executable) = message["executable"].as_str() {
+            let name = message["target"]["name"].as_str().unwrap_or_default();
+            artifacts.push(Artifact {
+                name: name.to_owned(),
+                manifest_path: message["manifest_path"].as_str().unwrap_or_default().into(),
+                bitcode: find_bitcode(Path::new(executable), name)?,
+            });
+        }
+    }
+
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("cargo build failed: {}", status));
+    }
+    Ok(artifacts)
+}
+
+/// Cargo reports the executable it copied out of `deps`, but the bitcode is only in `deps`, named
+/// after the crate and a hash cargo doesn't report.  The copy is a hard link to the original, so
+/// the bitcode is next to the file in `deps` that is the same file as the executable.
+fn find_bitcode(executable: &Path, name: &str) -> Result<PathBuf, String> {
+    let missing = || {
+        format!(
+            "couldn't find the bitcode for {};  is RUSTFLAGS overriding --emit?",
+            executable.display()
+        )
+    };
+    if executable.extension() == Some("bc".as_ref()) {
+        return Ok(executable.to_owned());
+    }
+    let sibling = executable.with_extension("bc");
+    if executable.parent().and_then(Path::file_name) == Some("deps".as_ref()) && sibling.exists() {
+        return Ok(sibling);
+    }
+
+    let deps = executable.parent().ok_or_else(missing)?.join("deps");
+    let prefix = format!("{}-", name.replace('-', "_"));
+    let entries =
+        fs::read_dir(&deps).map_err(|e| format!("couldn't read {}: {}", deps.display(), e))?;
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        if file_name.starts_with(&prefix)
+            && path.extension().is_none()
+            && same_file(&path, executable)
+        {
+            let bitcode = path.with_extension("bc");
+            return if bitcode.exists() {
+                Ok(bitcode)
+            } else {
+                Err(missing())
+            };
+        }
+    }
+    Err(missing())
+}
+
+#[cfg(unix)]
+fn same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+/// Cargo copies the executable when it can't hard link it, so compare the contents instead.
+#[cfg(not(unix))]
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::read(a), fs::read(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
@@ -0,0 +1,523 @@
+//! `graal-bindgen.toml`, which says what `cargo graal bindgen` generates and how, so the
+//! customizations survive regenerating the bindings for a new version of a library:
+//!
+//! ```toml
+//! include = ["com.example.**", "java.util.ArrayList", "com.google.guava:guava:32.1.2-jre"]
+//! exclude = ["com.example.internal.**"]
+//! output = "src/bindings.rs"
+//! snake-case = true
+//! exclude-methods = ["hashCode"]
+//!
+//! [classes."java.util.ArrayList"]
+//! module = "collections"
+//! exclude-methods = ["spliterator"]
+//!
+//! [classes."java.util.ArrayList".methods]
+//! "add" = { mut = true }
+//! "add(int, E)" = { rename = "add_at", mut = true }
+//! "get" = { nullable = true }
+//! "new(int)" = { rename = "with_capacity" }
+//! ```
+//!
+//! The keys that apply to classes can also be at the top level, where they apply to every class.
+//! Class sections and method keys can be globs, and every one that matches applies, so a method
+//! key with parameters overrides one without, and an exact class name overrides a glob.
+//! `package-modules = true` puts classes in modules named after their packages, like
+//! `java::util`, inside `module` if that's set too.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use toml::Value;
+
+use crate::bindgen::{is_identifier, split_top_level};
+
+pub const FILE_NAME: &str = "graal-bindgen.toml";
+
+/// A pattern where `*` matches anything but a `.`, `**` matches anything and `?` matches one
+/// character.
+#[derive(Clone, Debug)]
+pub struct Glob(String);
+
+impl Glob {
+    pub fn new(pattern: &str) -> Glob {
+        Glob(pattern.to_owned())
+    }
+
+    pub fn is_glob(pattern: &str) -> bool {
+        pattern.contains('*') || pattern.contains('?')
+    }
+
+    pub fn matches(&self, text: &str) -> bool {
+        glob_matches(self.0.as_bytes(), text.as_bytes())
+    }
+}
+
+fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_matches(rest, &text[i..])),
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'.')
+            .any(|i| glob_matches(rest, &text[i..])),
+        [b'?', rest @ ..] => !text.is_empty() && glob_matches(rest, &text[1..]),
+        [c, rest @ ..] => text.first() == Some(c) && glob_matches(rest, &text[1..]),
+    }
+}
+
+#[derive(Default)]
+pub struct Config {
+    /// Class names, globs, or Maven coordinates.
+    pub include: Vec<String>,
+    pub exclude: Vec<Glob>,
+    /// Relative to the directory containing the file.
+    pub output: Option<PathBuf>,
+    /// The top-level settings, then the class sections, least specific first.
+    classes: Vec<(Glob, ClassConfig)>,
+}
+
+/// The settings for a class, from every section that matches it.
+#[derive(Clone, Default)]
+pub struct ClassConfig {
+    /// The module the class goes in, like `collections` or `util::collections`.
+    pub module: Option<String>,
+    include_methods: Vec<Glob>,
+    exclude_methods: Vec<Glob>,
+    /// Whether methods returning objects return an `Option`.
+    nullable: Option<bool>,
+    /// Whether the class is generated with `#[snake_case]`.
+    pub snake_case: Option<bool>,
+    /// Whether the class goes in modules named after its package, inside `module`.
+    pub package_modules: Option<bool>,
+    methods: Vec<(MethodPattern, MethodConfig)>,
+}
+
+#[derive(Clone)]
+struct MethodPattern {
+    /// `new` for constructors.
+    name: Glob,
+    /// The parameter types, with packages left out, if the pattern has them.
+    params: Option<Vec<String>>,
+}
+
+/// The settings for a method, from every key that matches it.
+#[derive(Clone, Default)]
+pub struct MethodConfig {
+    pub rename: Option<String>,
+    pub mutating: Option<bool>,
+    /// Whether the method returns an `Option`.
+    pub nullable: Option<bool>,
+    /// Parameters that take an `Option`, by index.
+    pub nullable_params: Vec<usize>,
+}
+
+impl Config {
+    /// Reads `path`, or returns the default configuration if `path` doesn't exist.
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(_) if !path.exists() => return Ok(Config::default()),
+            Err(e) => return Err(format!("couldn't read {}: {}", path.display(), e)),
+        };
+        let error = |e: String| format!("{}: {}", path.display(), e);
+        let root: Value = text
+            .parse()
+            .map_err(|e: toml::de::Error| error(e.to_string()))?;
+        Config::from_toml(&root).map_err(error)
+    }
+
+    pub fn from_toml(root: &Value) -> Result<Config, String> {
+        let mut classes = vec![(Glob("**".to_owned()), ClassConfig::from_toml(root, "")?)];
+        if let Some(sections) = root.get("classes") {
+            let sections = sections.as_table().ok_or("classes must be a table")?;
+            let mut sections: Vec<(&String, &Value)> = sections.iter().collect();
+            // Exact names after globs, and longer globs after shorter ones.
+            sections.sort_by_key(|(pattern, _)| (!Glob::is_glob(pattern), pattern.len()));
+            for (pattern, section) in sections {
+                let context = format!("classes.\"{}\".", pattern);
+                classes.push((
+                    Glob(pattern.clone()),
+                    ClassConfig::from_toml(section, &context)?,
+                ));
+            }
+        }
+        Ok(Config {
+            include: strings(root, "include", "")?,
+            exclude: strings(root, "exclude", "")?
+                .into_iter()
+                .map(Glob)
+                .collect(),
+            output: string(root, "output", "")?.map(PathBuf::from),
+            classes,
+        })
+    }
+
+    pub fn excludes(&self, class: &str) -> bool {
+        self.exclude.iter().any(|glob| glob.matches(class))
+    }
+
+    /// The settings for `class`, merged from every section that matches it.
+    pub fn class(&self, class: &str) -> ClassConfig {
+        let mut merged = ClassConfig::default();
+        for (glob, config) in &self.classes {
+            if glob.matches(class) {
+                merged.module = config.module.clone().or(merged.module);
+                merged.nullable = config.nullable.or(merged.nullable);
+                merged.snake_case = config.snake_case.or(merged.snake_case);
+                merged.package_modules = config.package_modules.or(merged.package_modules);
+                merged
+                    .include_methods
+                    .extend(config.include_methods.iter().cloned());
+                merged
+                    .exclude_methods
+                    .extend(config.exclude_methods.iter().cloned());
+                merged.methods.extend(config.methods.iter().cloned());
+            }
+        }
+        // Keys with parameters override keys without them, whichever section they're in.
+        merged
+            .methods
+            .sort_by_key(|(pattern, _)| pattern.params.is_some());
+        merged
+    }
+}
+
+impl ClassConfig {
+    fn from_toml(table: &Value, context: &str) -> Result<ClassConfig, String> {
+        let mut methods = Vec::new();
+        if let Some(keys) = table.get("methods") {
+            let keys = keys
+                .as_table()
+                .ok_or_else(|| format!("{}methods must be a table", context))?;
+            for (key, method) in keys {
+                let context = format!("{}methods.\"{}\".", context, key);
+                methods.push((
+                    MethodPattern::parse(key),
+                    MethodConfig::from_toml(method, &context)?,
+                ));
+            }
+        }
+        Ok(ClassConfig {
+            module: string(table, "module", context)?,
+            include_methods: strings(table, "include-methods", context)?
+                .into_iter()
+                .map(Glob)
+                .collect(),
+            exclude_methods: strings(table, "exclude-methods", context)?
+                .into_iter()
+                .map(Glob)
+                .collect(),
+            nullable: boolean(table, "nullable", context)?,
+            snake_case: boolean(table, "snake-case", context)?,
+            package_modules: boolean(table, "package-modules", context)?,
+            methods,
+        })
+    }
+
+    /// Whether a method (or `new`, for constructors) should be generated.
+    pub fn includes_method(&self, name: &str) -> bool {
+        (self.include_methods.is_empty()
+            || self.include_methods.iter().any(|glob| glob.matches(name)))
+            && !self.exclude_methods.iter().any(|glob| glob.matches(name))
+    }
+
+    /// The settings for a method, given its parameter types as `javap` prints them.
+    pub fn method(&self, name: &str, params: &[String]) -> MethodConfig {
+        let params: Vec<String> = params.iter().map(|param| normalize_type(param)).collect();
+        let mut merged = MethodConfig {
+            nullable: self.nullable,
+            ..MethodConfig::default()
+        };
+        for (pattern, config) in &self.methods {
+            let matches = pattern.name.matches(name)
+                && pattern
+                    .params
+                    .as_ref()
+                    .is_none_or(|pattern| *pattern == params);
+            if matches {
+                merged.rename = config.rename.clone().or(merged.rename);
+                merged.mutating = config.mutating.or(merged.mutating);
+                merged.nullable = config.nullable.or(merged.nullable);
+                merged
+                    .nullable_params
+                    .extend(config.nullable_params.iter().cloned());
+            }
+        }
+        merged
+    }
+}
+
+/// The key that picks one overload in a `methods` table, like `add(int, E)`.
+pub fn method_key(name: &str, params: &[String]) -> String {
+    let params: Vec<String> = params.iter().map(|param| normalize_type(param)).collect();
+    format!("{}({})", name, params.join(", "))
+}
+
+impl MethodPattern {
+    /// `name` or `name(type, type)`
+    fn parse(key: &str) -> MethodPattern {
+        match key.find('(') {
+            Some(open) => MethodPattern {
+                name: Glob(key[..open].trim().to_owned()),
+                params: Some(
+                    split_top_level(key[open + 1..].trim_end().trim_end_matches(')'), ',')
+                        .iter()
+                        .map(|param| normalize_type(param))
+                        .collect(),
+                ),
+            },
+            None => MethodPattern {
+                name: Glob(key.trim().to_owned()),
+                params: None,
+            },
+        }
+    }
+}
+
+impl MethodConfig {
+    fn from_toml(table: &Value, context: &str) -> Result<MethodConfig, String> {
+        let rename = string(table, "rename", context)?;
+        if let Some(rename) = &rename {
+            if !is_identifier(rename) {
+                return Err(format!(
+                    "{}rename: {} isn't a Rust identifier, or is a keyword",
+                    context, rename
+                ));
+            }
+        }
+        let nullable_params = match table.get("nullable-params") {
+            None => Vec::new(),
+            Some(Value::Array(indices)) => indices
+                .iter()
+                .map(|index| {
+                    index
+                        .as_integer()
+                        .filter(|&index| index >= 0)
+                        .map(|index| index as usize)
+                        .ok_or_else(|| {
+                            format!("{}nullable-params must be parameter indices", context)
+                        })
+                })
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err(format!("{}nullable-params must be an array", context)),
+        };
+        Ok(MethodConfig {
+            rename,
+            mutating: boolean(table, "mut", context)?,
+            nullable: boolean(table, "nullable", context)?,
+            nullable_params,
+        })
+    }
+}
+
+/// A type without whitespace or packages, so `java.util.List<? extends E>` and `List<?extends E>`
+/// are the same.
+fn normalize_type(ty: &str) -> String {
+    let chars: Vec<char> = ty.chars().collect();
+    let mut normalized = String::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_alphanumeric() || c == '_' || c == '$' {
+            word.push(c);
+            continue;
+        }
+        // A lowercase word followed by a single `.` is a package, but `int...` isn't.
+        let package =
+            c == '.' && chars.get(i + 1) != Some(&'.') && word.starts_with(char::is_lowercase);
+        if !package {
+            normalized.push_str(&word);
+            if !c.is_whitespace() {
+                normalized.push(c);
+            }
+        }
+        word.clear();
+    }
+    normalized.push_str(&word);
+    normalized
+}
+
+fn string(table: &Value, key: &str, context: &str) -> Result<Option<String>, String> {
+    match table.get(key) {
+        None => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(_) => Err(format!("{}{} must be a string", context, key)),
+    }
+}
+
+fn boolean(table: &Value, key: &str, context: &str) -> Result<Option<bool>, String> {
+    match table.get(key) {
+        None => Ok(None),
+        Some(Value::Boolean(value)) => Ok(Some(*value)),
+        Some(_) => Err(format!("{}{} must be true or false", context, key)),
+    }
+}
+
+fn strings(table: &Value, key: &str, context: &str) -> Result<Vec<String>, String> {
+    let error = || format!("{}{} must be an array of strings", context, key);
+    match table.get(key) {
+        None => Ok(Vec::new()),
+        Some(Value::Array(values)) => values
+            .iter()
+            .map(|value| value.as_str().map(str::to_owned).ok_or_else(error))
+            .collect(),
+        Some(_) => Err(error()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toml: &str) -> Result<Config, String> {
+        Config::from_toml(&toml.parse().unwrap())
+    }
+
+    fn params(params: &[&str]) -> Vec<String> {
+        params.iter().map(|param| param.to_string()).collect()
+    }
+
+    #[test]
+    fn matches_globs() {
+        let matches = |pattern: &str, text: &str| Glob::new(pattern).matches(text);
+        assert!(matches("java.util.ArrayList", "java.util.ArrayList"));
+        assert!(!matches("java.util.ArrayList", "java.util.ArrayList2"));
+        assert!(matches("java.util.*", "java.util.ArrayList"));
+        assert!(!matches("java.util.*", "java.util.concurrent.Future"));
+        assert!(matches("java.util.**", "java.util.concurrent.Future"));
+        assert!(matches("**", ""));
+        assert!(matches("java.util.*List", "java.util.ArrayList"));
+        assert!(matches("get?", "getX"));
+        assert!(!matches("get?", "get"));
+        assert!(Glob::is_glob("com.example.**"));
+        assert!(!Glob::is_glob("com.example.Foo"));
+    }
+
+    #[test]
+    fn normalizes_types() {
+        assert_eq!(
+            normalize_type("java.util.List<? extends E>"),
+            "List<?extendsE>"
+        );
+        assert_eq!(normalize_type("List<?extends E>"), "List<?extendsE>");
+        assert_eq!(
+            normalize_type("java.util.Map$Entry<K, V>"),
+            "Map$Entry<K,V>"
+        );
+        assert_eq!(normalize_type("java.lang.Object..."), "Object...");
+        assert_eq!(normalize_type("int[]"), "int[]");
+        assert_eq!(method_key("add", &params(&["int", "E"])), "add(int, E)");
+    }
+
+    #[test]
+    fn parses_method_patterns() {
+        let pattern = MethodPattern::parse("add");
+        assert_eq!(pattern.name.0, "add");
+        assert_eq!(pattern.params, None);
+        let pattern = MethodPattern::parse(" addAll (int, java.util.Collection<? extends E>) ");
+        assert_eq!(pattern.name.0, "addAll");
+        assert_eq!(
+            pattern.params,
+            Some(params(&["int", "Collection<?extendsE>"]))
+        );
+        assert_eq!(MethodPattern::parse("clear()").params, Some(Vec::new()));
+        let pattern = MethodPattern::parse("put(Map<K, V>, V)");
+        assert_eq!(pattern.params, Some(params(&["Map<K,V>", "V"])));
+    }
+
+    #[test]
+    fn merges_class_sections() {
+        let config = config(
+            r#"
+            include = ["java.util.*"]
+            exclude = ["java.util.Internal*"]
+            module = "java"
+            exclude-methods = ["hashCode"]
+
+            [classes."java.util.*"]
+            module = "util"
+            snake-case = true
+
+            [classes."java.util.ArrayList"]
+            module = "lists"
+            include-methods = ["add*", "hashCode"]
+            "#,
+        )
+        .unwrap();
+        assert!(config.excludes("java.util.InternalList"));
+        assert!(!config.excludes("java.util.ArrayList"));
+
+        let list = config.class("java.util.ArrayList");
+        assert_eq!(list.module.as_deref(), Some("lists"));
+        assert_eq!(list.snake_case, Some(true));
+        assert!(list.includes_method("addAll"));
+        assert!(!list.includes_method("get"));
+        assert!(!list.includes_method("hashCode"));
+
+        let date = config.class("java.sql.Date");
+        assert_eq!(date.module.as_deref(), Some("java"));
+        assert_eq!(date.snake_case, None);
+        assert!(date.includes_method("getTime"));
+    }
+
+    #[test]
+    fn keys_with_parameters_override_keys_without() {
+        let config = config(
+            r#"
+            nullable = true
+
+            [classes."java.util.ArrayList".methods]
+            "add(int, E)" = { rename = "add_at", mut = true }
+            "add" = { mut = false, nullable = false }
+            "set" = { nullable-params = [1] }
+            "#,
+        )
+        .unwrap();
+        let list = config.class("java.util.ArrayList");
+        let add_at = list.method("add", &params(&["int", "E"]));
+        assert_eq!(add_at.rename.as_deref(), Some("add_at"));
+        assert_eq!(add_at.mutating, Some(true));
+        assert_eq!(add_at.nullable, Some(false));
+        let add = list.method("add", &params(&["E"]));
+        assert_eq!(add.rename, None);
+        assert_eq!(add.mutating, Some(false));
+        assert_eq!(list.method("get", &params(&["int"])).nullable, Some(true));
+        assert_eq!(
+            list.method("set", &params(&["int", "E"])).nullable_params,
+            [1]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_settings() {
+        let error = |toml: &str| config(toml).err().unwrap();
+        assert_eq!(error("module = 1"), "module must be a string");
+        assert_eq!(
+            error(
+                r#"[classes."A"]
+                     snake-case = "yes""#
+            ),
+            "classes.\"A\".snake-case must be true or false"
+        );
+        assert_eq!(
+            error(
+                r#"[classes."A".methods]
+                     "get" = { rename = "fn" }"#
+            ),
+            "classes.\"A\".methods.\"get\".rename: fn isn't a Rust identifier, or is a keyword"
+        );
+        assert_eq!(
+            error(
+                r#"[classes."A".methods]
+                     "get" = { rename = "gen" }"#
+            ),
+            "classes.\"A\".methods.\"get\".rename: gen isn't a Rust identifier, or is a keyword"
+        );
+        assert_eq!(
+            error(
+                r#"[classes."A".methods]
+                     "get" = { nullable-params = [-1] }"#
+            ),
+            "classes.\"A\".methods.\"get\".nullable-params must be parameter indices"
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// How to run bitcode with `lli`.
+pub struct Lli {
+    pub path: PathBuf,
+    /// Rust's standard library, which the bitcode links against dynamically.
+    libstd: PathBuf,
+    /// Options for `lli` itself, like `--vm.Xmx2g` or `--js.strict`.
+    options: Vec<String>,
+}
+
+impl Lli {
+    pub fn new(path: PathBuf, options: Vec<String>) -> Result<Lli, String> {
+        Ok(Lli {
+            path,
+            libstd: libstd()?,
+            options,
+        })
+    }
+
+    /// `lli` running `bitcode`.  Arguments added to the command are passed to the program.
+    pub fn command(&self, bitcode: &Path) -> Command {
+        let mut command = Command::new(&self.path);
+        command
+            .arg("--polyglot")
+            .arg("--lib")
+            .arg(&self.libstd)
+            .args(&self.options)
+            .arg(bitcode);
+        command
+    }
+}
+
+/// `--vm.OPTION`, which passes `OPTION` to the JVM `lli` runs on.  Leading dashes are dropped, so
+/// `Xmx2g` and `-Xmx2g` are the same.
+pub fn vm_option(option: &str) -> String {
+    format!("--vm.{}", option.trim_start_matches('-'))
+}
+
+/// Finds the shared `libstd` in the sysroot of the `rustc` that builds the crate.  Older toolchains
+/// put it in `lib`, and newer ones only in `lib/rustlib/<host>/lib`.
+fn libstd() -> Result<PathBuf, String> {
+    let rustc = std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let output = Command::new(rustc)
+        .args(["--print", "sysroot"])
+        .output()
+        .map_err(|e| format!("couldn't run rustc: {}", e))?;
+    let lib = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()).join("lib");
+    let mut dirs = vec![lib.clone()];
+    if let Ok(targets) = fs::read_dir(lib.join("rustlib")) {
+        dirs.extend(
+            targets
+                .filter_map(Result::ok)
+                .map(|target| target.path().join("lib")),
+        );
+    }
+    dirs.iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.filter_map(Result::ok).map(|entry| entry.path()))
+        .find(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.starts_with("libstd-") && (name.ends_with(".so") || name.ends_with(".dylib"))
+        })
+        .ok_or_else(|| format!("couldn't find libstd in {}", lib.display()))
+}
@@ -0,0 +1,182 @@
+//! `cargo graal` builds a graal-bindgen crate to bitcode with GraalVM's LLVM toolchain, and runs
+//! it or its `#[graal_test]` tests with `lli`.  It also generates `class!` bindings for Java
+//! classes.
+//!
+//! ```bash
+//! cargo graal build [CARGO OPTIONS]
+//! cargo graal run [--vm OPTION]... [--lli-arg ARG]... [CARGO OPTIONS] [-- PROGRAM ARGS]
+//! cargo graal test [--vm OPTION]... [--lli-arg ARG]... [CARGO OPTIONS] [-- TEST OPTIONS]
+//! cargo graal bindgen [--manifest-path PATH] [--config FILE] [-o FILE] (CLASS | GLOB | GROUP:ARTIFACT:VERSION)...
+//! ```
+//!
+//! The classpath, Maven dependencies and JVM options in the crate's
+//! `[package.metadata.graal-bindgen]` are passed to `lli` and `javap`, and `bindgen` is configured
+//! by the crate's `graal-bindgen.toml`.
+
+mod bindgen;
+mod build;
+mod config;
+mod lli;
+mod maven;
+mod metadata;
+#[path = "../../graal-bindgen-macros/src/names.rs"]
+mod names;
+mod test;
+
+use std::env;
+use std::process;
+
+use build::{Artifact, Toolchain};
+use lli::Lli;
+use metadata::Metadata;
+
+const USAGE: &str = "usage:
+    cargo graal build [CARGO OPTIONS]
+    cargo graal run [--vm OPTION]... [--lli-arg ARG]... [CARGO OPTIONS] [-- PROGRAM ARGS]
+    cargo graal test [--vm OPTION]... [--lli-arg ARG]... [CARGO OPTIONS] [-- TEST OPTIONS]
+    cargo graal bindgen [--manifest-path PATH] [--config FILE] [-o FILE] (CLASS | GLOB | GROUP:ARTIFACT:VERSION)...
+
+--vm OPTION     passes --vm.OPTION to lli, like `--vm Xmx2g`
+--lli-arg ARG   passes ARG to lli, like `--lli-arg --jvm` or `--lli-arg --js.strict`
+Other options before `--` are passed to `cargo build`.";
+
+/// The feature `graal_test_main!` generates the test entry point under.
+const TEST_FEATURE: &str = "graal-test";
+
+enum Subcommand {
+    Build,
+    Run,
+    Test,
+    Bindgen,
+}
+
+struct Options {
+    subcommand: Subcommand,
+    lli_options: Vec<String>,
+    cargo_args: Vec<String>,
+    /// Arguments after `--`, for the program or the test runner.
+    trailing_args: Vec<String>,
+}
+
+impl Options {
+    fn parse() -> Result<Options, String> {
+        let mut args = env::args().skip(1).peekable();
+        // Cargo runs `cargo-graal graal ...` for `cargo graal ...`.
+        if args.peek().map(String::as_str) == Some("graal") {
+            args.next();
+        }
+        let subcommand = match args.next().as_deref() {
+            Some("build") => Subcommand::Build,
+            Some("run") => Subcommand::Run,
+            Some("test") => Subcommand::Test,
+            Some("bindgen") => Subcommand::Bindgen,
+            _ => return Err(USAGE.to_owned()),
+        };
+
+        let mut lli_options = Vec::new();
+        let mut cargo_args = Vec::new();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--" => break,
+                "--vm" => {
+                    let option = args.next().ok_or("--vm needs an option")?;
+                    lli_options.push(lli::vm_option(&option));
+                }
+                "--lli-arg" => lli_options.push(args.next().ok_or("--lli-arg needs an argument")?),
+                "-h" | "--help" => return Err(USAGE.to_owned()),
+                _ => cargo_args.push(arg),
+            }
+        }
+        if let (Subcommand::Build | Subcommand::Bindgen, false) =
+            (&subcommand, lli_options.is_empty())
+        {
+            return Err("--vm and --lli-arg only apply to `run` and `test`".to_owned());
+        }
+
+        Ok(Options {
+            subcommand,
+            lli_options,
+            cargo_args,
+            trailing_args: args.collect(),
+        })
+    }
+}
+
+/// The one binary cargo built, or an error asking for `--bin` if it built several.
+fn single_binary(artifacts: Vec<Artifact>) -> Result<Artifact, String> {
+    match artifacts.len() {
+        0 => Err("cargo didn't build a binary".to_owned()),
+        1 => Ok(artifacts.into_iter().next().unwrap()),
+        _ => {
+            let names: Vec<&str> = artifacts.iter().map(|a| a.name.as_str()).collect();
+            Err(format!(
+                "pass --bin to pick one of the binaries: {}",
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+/// The options for `lli`:  those from the crate's metadata, then those on the command line.
+fn lli(toolchain: &Toolchain, artifact: &Artifact, options: Vec<String>) -> Result<Lli, String> {
+    let mut lli_options = Metadata::load(&artifact.manifest_path)?.lli_options()?;
+    lli_options.extend(options);
+    Lli::new(toolchain.lli(), lli_options)
+}
+
+fn run(options: Options) -> Result<i32, String> {
+    if let Subcommand::Bindgen = options.subcommand {
+        return bindgen::run(&options.cargo_args).map(|()| 0);
+    }
+    let toolchain = Toolchain::from_env()?;
+    let mut cargo_args = options.cargo_args;
+    if let Subcommand::Test = options.subcommand {
+        cargo_args.push("--features".to_owned());
+        cargo_args.push(TEST_FEATURE.to_owned());
+    }
+    let artifacts = build::build(&toolchain, &cargo_args)?;
+
+    match options.subcommand {
+        Subcommand::Bindgen => unreachable!(),
+        Subcommand::Build => {
+            for artifact in &artifacts {
+                println!("{}", artifact.bitcode.display());
+            }
+            Ok(0)
+        }
+        Subcommand::Run => {
+            let artifact = single_binary(artifacts)?;
+            let lli = lli(&toolchain, &artifact, options.lli_options)?;
+            let status = lli
+                .command(&artifact.bitcode)
+                .args(&options.trailing_args)
+                .status()
+                .map_err(|e| format!("couldn't run {}: {}", lli.path.display(), e))?;
+            Ok(status.code().unwrap_or(101))
+        }
+        Subcommand::Test => {
+            let artifact = single_binary(artifacts)?;
+            let lli = lli(&toolchain, &artifact, options.lli_options)?;
+            test::run(&lli, &artifact.bitcode, &options.trailing_args)
+        }
+    }
+}
+
+/// Prints a warning about something that doesn't stop the command.
+fn warn(message: String) {
+    eprintln!("warning: {}", message);
+}
+
+fn main() {
+    let options = Options::parse().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(2);
+    });
+    match run(options) {
+        Ok(code) => process::exit(code),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(101);
+        }
+    }
+}
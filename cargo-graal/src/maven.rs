@@ -0,0 +1,724 @@
+//! Resolves Maven coordinates against a local repository, like `~/.m2/repository`, without
+//! downloading anything.
+//!
+//! Each artifact's POM is read for its compile-scope dependencies, which are resolved the same way.
+//! Like Maven, the nearest declaration of an artifact wins, and `<exclusions>`, `<optional>`,
+//! `<dependencyManagement>` (including imported BOMs), parent POMs and `${property}` references
+//! are honored.  Dependencies that aren't in the repository are skipped with a warning, since
+//! they'd have to be downloaded.
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::warn;
+
+/// `groupId:artifactId[:packaging[:classifier]]:version`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Coordinates {
+    pub group: String,
+    pub artifact: String,
+    pub packaging: String,
+    pub classifier: Option<String>,
+    pub version: String,
+}
+
+impl Coordinates {
+    pub fn parse(coordinates: &str) -> Result<Coordinates, String> {
+        let parts: Vec<&str> = coordinates.split(':').collect();
+        let (packaging, classifier) = match parts.len() {
+            3 => ("jar", None),
+            4 => (parts[2], None),
+            5 => (parts[2], Some(parts[3].to_owned())),
+            _ => {
+                return Err(format!(
+                    "{} isn't groupId:artifactId[:packaging[:classifier]]:version",
+                    coordinates
+                ))
+            }
+        };
+        if parts.iter().any(|part| part.is_empty()) {
+            return Err(format!("{} has an empty part", coordinates));
+        }
+        Ok(Coordinates {
+            group: parts[0].to_owned(),
+            artifact: parts[1].to_owned(),
+            packaging: packaging.to_owned(),
+            classifier,
+            version: parts[parts.len() - 1].to_owned(),
+        })
+    }
+
+    /// `groupId:artifactId[:classifier]`, which identifies the artifact regardless of its
+    /// version.  A classified artifact, like a jar of natives, is a separate file from the main
+    /// one, so it has its own key.
+    fn key(&self) -> String {
+        match &self.classifier {
+            Some(classifier) => format!("{}:{}:{}", self.group, self.artifact, classifier),
+            None => format!("{}:{}", self.group, self.artifact),
+        }
+    }
+
+    /// Whether a `groupId:artifactId` exclusion, either of which may be `*`, matches these.
+    fn is_excluded_by(&self, exclusion: &str) -> bool {
+        let (group, artifact) = exclusion.split_once(':').unwrap_or((exclusion, "*"));
+        (group == "*" || group == self.group) && (artifact == "*" || artifact == self.artifact)
+    }
+}
+
+impl fmt::Display for Coordinates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.group, self.artifact)?;
+        if self.packaging != "jar" || self.classifier.is_some() {
+            write!(f, ":{}", self.packaging)?;
+        }
+        if let Some(classifier) = &self.classifier {
+            write!(f, ":{}", classifier)?;
+        }
+        write!(f, ":{}", self.version)
+    }
+}
+
+/// A repository with Maven's layout, like `~/.m2/repository`.
+pub struct Repository {
+    root: PathBuf,
+}
+
+impl Repository {
+    pub fn new(root: PathBuf) -> Repository {
+        Repository { root }
+    }
+
+    /// `~/.m2/repository`, or `None` if there's no home directory.
+    pub fn default_root() -> Option<PathBuf> {
+        env::var_os("HOME")
+            .or_else(|| env::var_os("USERPROFILE"))
+            .map(|home| Path::new(&home).join(".m2/repository"))
+    }
+
+    fn directory(&self, coordinates: &Coordinates) -> PathBuf {
+        let mut directory = self.root.clone();
+        directory.extend(coordinates.group.split('.'));
+        directory.push(&coordinates.artifact);
+        directory.push(&coordinates.version);
+        directory
+    }
+
+    /// The artifact's file, like `.../guava/32.1.2-jre/guava-32.1.2-jre.jar`.
+    pub fn artifact(&self, coordinates: &Coordinates) -> PathBuf {
+        let classifier = coordinates
+            .classifier
+            .as_ref()
+            .map(|classifier| format!("-{}", classifier))
+            .unwrap_or_default();
+        let extension = match coordinates.packaging.as_str() {
+            "bundle" | "maven-plugin" => "jar",
+            packaging => packaging,
+        };
+        self.directory(coordinates).join(format!(
+            "{}-{}{}.{}",
+            coordinates.artifact, coordinates.version, classifier, extension
+        ))
+    }
+
+    fn pom_path(&self, coordinates: &Coordinates) -> PathBuf {
+        self.directory(coordinates).join(format!(
+            "{}-{}.pom",
+            coordinates.artifact, coordinates.version
+        ))
+    }
+
+    /// Reads a POM along with its parents, or `None` if it isn't in the repository.
+    fn pom(&self, coordinates: &Coordinates) -> Result<Option<Pom>, String> {
+        self.pom_with_depth(coordinates, 0)
+    }
+
+    fn pom_with_depth(
+        &self,
+        coordinates: &Coordinates,
+        depth: usize,
+    ) -> Result<Option<Pom>, String> {
+        if depth > 32 {
+            return Err(format!("{} has too many parent POMs", coordinates));
+        }
+        let path = self.pom_path(coordinates);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => return Ok(None),
+        };
+        let document = roxmltree::Document::parse(&text)
+            .map_err(|e| format!("couldn't parse {}: {}", path.display(), e))?;
+        let project = document.root_element();
+
+        let parent = match child(project, "parent") {
+            Some(parent) => {
+                let parent = Coordinates {
+                    group: text_of(parent, "groupId").unwrap_or_default(),
+                    artifact: text_of(parent, "artifactId").unwrap_or_default(),
+                    packaging: "pom".to_owned(),
+                    classifier: None,
+                    version: text_of(parent, "version").unwrap_or_default(),
+                };
+                match self.pom_with_depth(&parent, depth + 1)? {
+                    Some(pom) => Some(pom),
+                    None => {
+                        warn(format!(
+                            "parent POM {} of {} isn't in the repository",
+                            parent, coordinates
+                        ));
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let mut properties = parent
+            .as_ref()
+            .map(|parent| parent.properties.clone())
+            .unwrap_or_default();
+        if let Some(element) = child(project, "properties") {
+            for property in element.children().filter(|node| node.is_element()) {
+                properties.insert(
+                    property.tag_name().name().to_owned(),
+                    property.text().unwrap_or_default().trim().to_owned(),
+                );
+            }
+        }
+        let group = text_of(project, "groupId")
+            .or_else(|| parent.as_ref().map(|parent| parent.group.clone()))
+            .unwrap_or_else(|| coordinates.group.clone());
+        let version = text_of(project, "version")
+            .or_else(|| parent.as_ref().map(|parent| parent.version.clone()))
+            .unwrap_or_else(|| coordinates.version.clone());
+        for (name, value) in &[
+            ("groupId", &group),
+            ("artifactId", &coordinates.artifact),
+            ("version", &version),
+        ] {
+            properties.insert(format!("project.{}", name), value.to_string());
+            properties.insert(format!("pom.{}", name), value.to_string());
+        }
+        if let Some(parent) = &parent {
+            properties.insert("project.parent.groupId".to_owned(), parent.group.clone());
+            properties.insert("project.parent.version".to_owned(), parent.version.clone());
+        }
+
+        let mut pom = Pom {
+            group,
+            version,
+            properties,
+            managed: parent
+                .as_ref()
+                .map(|parent| parent.managed.clone())
+                .unwrap_or_default(),
+            dependencies: parent.map(|parent| parent.dependencies).unwrap_or_default(),
+        };
+        if let Some(management) = child(project, "dependencyManagement") {
+            for dependency in dependencies(management) {
+                let dependency = pom.dependency(dependency);
+                if dependency.scope == "import" {
+                    self.import(&mut pom, &dependency.coordinates, depth)?;
+                } else {
+                    pom.managed.insert(dependency.coordinates.key(), dependency);
+                }
+            }
+        }
+        for dependency in dependencies(project) {
+            let dependency = pom.dependency(dependency);
+            pom.dependencies.push(dependency);
+        }
+        Ok(Some(pom))
+    }
+
+    /// Adds the `<dependencyManagement>` of a BOM imported with `<scope>import</scope>`.
+    fn import(&self, pom: &mut Pom, bom: &Coordinates, depth: usize) -> Result<(), String> {
+        match self.pom_with_depth(bom, depth + 1)? {
+            Some(bom) => {
+                for (key, dependency) in bom.managed {
+                    pom.managed.entry(key).or_insert(dependency);
+                }
+            }
+            None => warn(format!("imported BOM {} isn't in the repository", bom)),
+        }
+        Ok(())
+    }
+
+    /// The files of `roots` and their compile-scope dependencies, nearest first.
+    pub fn resolve(&self, roots: &[Coordinates]) -> Result<Vec<PathBuf>, String> {
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<(Coordinates, Vec<String>, bool)> = roots
+            .iter()
+            .map(|root| (root.clone(), Vec::new(), true))
+            .collect();
+        let mut files = Vec::new();
+        // Versions `<dependencyManagement>` in the roots pins, which win over transitive ones.
+        let mut root_managed = BTreeMap::new();
+        for root in roots {
+            if let Some(pom) = self.pom(root)? {
+                for (key, dependency) in pom.managed {
+                    root_managed.entry(key).or_insert(dependency);
+                }
+            }
+        }
+
+        while let Some((mut coordinates, exclusions, is_root)) = queue.pop_front() {
+            if !is_root {
+                if let Some(managed) = root_managed.get(&coordinates.key()) {
+                    coordinates.version = managed.coordinates.version.clone();
+                }
+            }
+            if !seen.insert(coordinates.key()) {
+                continue;
+            }
+            let file = self.artifact(&coordinates);
+            if !file.exists() {
+                if is_root {
+                    return Err(format!(
+                        "{} isn't in {};  fetch it with Maven first",
+                        coordinates,
+                        self.root.display()
+                    ));
+                }
+                warn(format!(
+                    "{} isn't in the local repository, so it's left out",
+                    coordinates
+                ));
+                continue;
+            }
+            if coordinates.packaging != "pom" {
+                files.push(file);
+            }
+
+            let pom = match self.pom(&coordinates)? {
+                Some(pom) => pom,
+                None => continue,
+            };
+            for dependency in &pom.dependencies {
+                if dependency.optional || !matches!(dependency.scope.as_str(), "" | "compile") {
+                    continue;
+                }
+                let mut dependency = dependency.clone();
+                if dependency.coordinates.version.is_empty() {
+                    match pom.managed.get(&dependency.coordinates.key()) {
+                        Some(managed) => {
+                            dependency.coordinates.version = managed.coordinates.version.clone()
+                        }
+                        None => {
+                            warn(format!(
+                                "{} depends on {} without a version",
+                                coordinates,
+                                dependency.coordinates.key()
+                            ));
+                            continue;
+                        }
+                    }
+                }
+                if exclusions
+                    .iter()
+                    .any(|exclusion| dependency.coordinates.is_excluded_by(exclusion))
+                {
+                    continue;
+                }
+                if dependency.coordinates.version.starts_with('[')
+                    || dependency.coordinates.version.starts_with('(')
+                {
+                    warn(format!(
+                        "{} depends on {} with a version range, which isn't supported",
+                        coordinates, dependency.coordinates
+                    ));
+                    continue;
+                }
+                let mut exclusions = exclusions.clone();
+                exclusions.extend(dependency.exclusions);
+                queue.push_back((dependency.coordinates, exclusions, false));
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// The parts of a POM that matter for resolving dependencies, with its parents merged in.
+struct Pom {
+    group: String,
+    version: String,
+    properties: BTreeMap<String, String>,
+    /// `<dependencyManagement>`, by `Coordinates::key`.
+    managed: BTreeMap<String, Dependency>,
+    dependencies: Vec<Dependency>,
+}
+
+#[derive(Clone)]
+struct Dependency {
+    /// The version is empty if the POM leaves it to `<dependencyManagement>`.
+    coordinates: Coordinates,
+    scope: String,
+    optional: bool,
+    /// `groupId:artifactId`, either of which may be `*`.
+    exclusions: Vec<String>,
+}
+
+impl Pom {
+    fn dependency(&self, element: roxmltree::Node) -> Dependency {
+        let text = |name| {
+            text_of(element, name)
+                .map(|text| self.interpolate(&text))
+                .unwrap_or_default()
+        };
+        let classifier = text("classifier");
+        let packaging = text("type");
+        Dependency {
+            coordinates: Coordinates {
+                group: text("groupId"),
+                artifact: text("artifactId"),
+                packaging: match packaging.as_str() {
+                    "" | "test-jar" => "jar".to_owned(),
+                    _ => packaging,
+                },
+                classifier: Some(classifier).filter(|classifier| !classifier.is_empty()),
+                version: text("version"),
+            },
+            scope: text("scope"),
+            optional: text("optional") == "true",
+            exclusions: child(element, "exclusions")
+                .map(|exclusions| {
+                    exclusions
+                        .children()
+                        .filter(|node| node.has_tag_name("exclusion"))
+                        .map(|exclusion| {
+                            format!(
+                                "{}:{}",
+                                text_of(exclusion, "groupId").unwrap_or_default(),
+                                text_of(exclusion, "artifactId").unwrap_or_default()
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Replaces `${property}` references, leaving unknown ones alone.
+    fn interpolate(&self, text: &str) -> String {
+        let mut text = text.to_owned();
+        // Properties can refer to other properties, so keep going until nothing changes.
+        for _ in 0..8 {
+            let mut result = String::new();
+            let mut rest = text.as_str();
+            while let Some(start) = rest.find("${") {
+                let end = match rest[start..].find('}') {
+                    Some(end) => start + end,
+                    None => break,
+                };
+                result.push_str(&rest[..start]);
+                match self.properties.get(&rest[start + 2..end]) {
+                    Some(value) => result.push_str(value),
+                    None => result.push_str(&rest[start..=end]),
+                }
+                rest = &rest[end + 1..];
+            }
+            result.push_str(rest);
+            if result == text {
+                break;
+            }
+            text = result;
+        }
+        text
+    }
+}
+
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn text_of(node: roxmltree::Node, name: &str) -> Option<String> {
+    child(node, name)
+        .and_then(|child| child.text())
+        .map(|text| text.trim().to_owned())
+}
+
+fn dependencies<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> {
+    child(node, "dependencies")
+        .into_iter()
+        .flat_map(|dependencies| dependencies.children())
+        .filter(|node| node.has_tag_name("dependency"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A repository in a temporary directory, which is removed when it's dropped.
+    struct TestRepository {
+        repository: Repository,
+    }
+
+    impl TestRepository {
+        fn new(name: &str) -> TestRepository {
+            let root =
+                env::temp_dir().join(format!("cargo-graal-maven-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&root);
+            TestRepository {
+                repository: Repository::new(root),
+            }
+        }
+
+        /// Adds an artifact's POM, and its jar unless it's a `pom`.
+        fn add(&self, coordinates: &str, pom: &str) -> &TestRepository {
+            let coordinates = Coordinates::parse(coordinates).unwrap();
+            fs::create_dir_all(self.repository.directory(&coordinates)).unwrap();
+            let pom = format!("<project>{}</project>", pom);
+            fs::write(self.repository.pom_path(&coordinates), pom).unwrap();
+            if coordinates.packaging != "pom" {
+                fs::write(self.repository.artifact(&coordinates), "").unwrap();
+            }
+            self
+        }
+
+        /// The file names of what `root` resolves to.
+        fn resolve(&self, root: &str) -> Vec<String> {
+            let root = Coordinates::parse(root).unwrap();
+            let files = self.repository.resolve(&[root]).unwrap();
+            files
+                .iter()
+                .map(|file| file.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        }
+    }
+
+    impl Drop for TestRepository {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.repository.root);
+        }
+    }
+
+    fn dependency(coordinates: &str, extra: &str) -> String {
+        let parts: Vec<&str> = coordinates.split(':').collect();
+        let version = parts
+            .get(2)
+            .map(|version| format!("<version>{}</version>", version))
+            .unwrap_or_default();
+        format!(
+            "<dependency><groupId>{}</groupId><artifactId>{}</artifactId>{}{}</dependency>",
+            parts[0], parts[1], version, extra
+        )
+    }
+
+    #[test]
+    fn parses_coordinates() {
+        let coordinates = Coordinates::parse("com.google.guava:guava:32.1.2-jre").unwrap();
+        assert_eq!(coordinates.group, "com.google.guava");
+        assert_eq!(coordinates.artifact, "guava");
+        assert_eq!(coordinates.packaging, "jar");
+        assert_eq!(coordinates.classifier, None);
+        assert_eq!(coordinates.version, "32.1.2-jre");
+        assert_eq!(coordinates.to_string(), "com.google.guava:guava:32.1.2-jre");
+
+        let coordinates = Coordinates::parse("org.example:natives:jar:linux:1.0").unwrap();
+        assert_eq!(coordinates.classifier.as_deref(), Some("linux"));
+        assert_eq!(coordinates.to_string(), "org.example:natives:jar:linux:1.0");
+        let coordinates = Coordinates::parse("org.example:bom:pom:1.0").unwrap();
+        assert_eq!(coordinates.packaging, "pom");
+        assert_eq!(coordinates.to_string(), "org.example:bom:pom:1.0");
+
+        assert!(Coordinates::parse("org.example:guava").is_err());
+        assert!(Coordinates::parse("org.example::1.0").is_err());
+    }
+
+    #[test]
+    fn lays_out_artifacts_like_maven() {
+        let repository = Repository::new(PathBuf::from("/m2"));
+        let coordinates = Coordinates::parse("org.example.lib:core:bundle:tests:1.0").unwrap();
+        assert_eq!(
+            repository.artifact(&coordinates),
+            Path::new("/m2/org/example/lib/core/1.0/core-1.0-tests.jar")
+        );
+        assert_eq!(
+            repository.pom_path(&coordinates),
+            Path::new("/m2/org/example/lib/core/1.0/core-1.0.pom")
+        );
+    }
+
+    #[test]
+    fn interpolates_properties() {
+        let repository = TestRepository::new("interpolation");
+        repository.add(
+            "org.example:app:1.0",
+            "<groupId>org.example</groupId>
+             <properties>
+                 <lib.version>2.${lib.minor}</lib.version>
+                 <lib.minor>5</lib.minor>
+             </properties>",
+        );
+        let pom = repository
+            .repository
+            .pom(&Coordinates::parse("org.example:app:1.0").unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(pom.interpolate("${lib.version}"), "2.5");
+        assert_eq!(
+            pom.interpolate("${project.groupId}:${project.version}"),
+            "org.example:1.0"
+        );
+        assert_eq!(pom.interpolate("${unknown} ${"), "${unknown} ${");
+    }
+
+    #[test]
+    fn inherits_from_parent_poms() {
+        let repository = TestRepository::new("parents");
+        repository
+            .add(
+                "org.example:grandparent:pom:3",
+                &format!(
+                    "<groupId>org.example</groupId><version>3</version>
+                     <properties><lib.version>1.2</lib.version></properties>
+                     <dependencyManagement><dependencies>{}</dependencies></dependencyManagement>",
+                    dependency("org.example:lib:${lib.version}", "")
+                ),
+            )
+            .add(
+                "org.example:parent:pom:2",
+                "<parent>
+                     <groupId>org.example</groupId><artifactId>grandparent</artifactId>
+                     <version>3</version>
+                 </parent>
+                 <version>2</version>",
+            )
+            .add(
+                "org.example:app:1.0",
+                &format!(
+                    "<parent>
+                         <groupId>org.example</groupId><artifactId>parent</artifactId>
+                         <version>2</version>
+                     </parent>
+                     <dependencies>{}</dependencies>",
+                    dependency("${project.parent.groupId}:lib", "")
+                ),
+            )
+            .add("org.example:lib:1.2", "");
+        assert_eq!(
+            repository.resolve("org.example:app:1.0"),
+            ["app-1.0.jar", "lib-1.2.jar"]
+        );
+    }
+
+    #[test]
+    fn honors_exclusions_optional_and_scopes() {
+        let repository = TestRepository::new("exclusions");
+        let exclusion = "<exclusions>
+                             <exclusion>
+                                 <groupId>org.excluded</groupId><artifactId>*</artifactId>
+                             </exclusion>
+                             <exclusion><groupId>*</groupId><artifactId>b</artifactId></exclusion>
+                         </exclusions>";
+        repository
+            .add(
+                "org.example:app:1.0",
+                &format!(
+                    "<dependencies>{}{}{}</dependencies>",
+                    dependency("org.example:lib:1.0", exclusion),
+                    dependency("org.example:optional:1.0", "<optional>true</optional>"),
+                    dependency("org.example:test:1.0", "<scope>test</scope>"),
+                ),
+            )
+            .add(
+                "org.example:lib:1.0",
+                &format!(
+                    "<dependencies>{}{}{}</dependencies>",
+                    dependency("org.excluded:a:1.0", ""),
+                    dependency("org.other:b:1.0", ""),
+                    dependency("org.example:kept:1.0", ""),
+                ),
+            )
+            .add("org.excluded:a:1.0", "")
+            .add("org.other:b:1.0", "")
+            .add("org.example:kept:1.0", "")
+            .add("org.example:optional:1.0", "")
+            .add("org.example:test:1.0", "");
+        assert_eq!(
+            repository.resolve("org.example:app:1.0"),
+            ["app-1.0.jar", "lib-1.0.jar", "kept-1.0.jar"]
+        );
+    }
+
+    #[test]
+    fn keeps_classified_artifacts_apart() {
+        let repository = TestRepository::new("classifiers");
+        repository
+            .add(
+                "org.example:app:1.0",
+                &format!(
+                    "<dependencies>{}{}</dependencies>",
+                    dependency("org.example:lib:1.0", ""),
+                    dependency(
+                        "org.example:lib:1.0",
+                        "<classifier>natives-linux</classifier>"
+                    ),
+                ),
+            )
+            .add("org.example:lib:1.0", "")
+            .add("org.example:lib:jar:natives-linux:1.0", "");
+        assert_eq!(
+            repository.resolve("org.example:app:1.0"),
+            ["app-1.0.jar", "lib-1.0.jar", "lib-1.0-natives-linux.jar"]
+        );
+    }
+
+    #[test]
+    fn takes_versions_from_imported_boms_and_the_root() {
+        let repository = TestRepository::new("boms");
+        repository
+            .add(
+                "org.example:bom:pom:1.0",
+                &format!(
+                    "<dependencyManagement><dependencies>{}{}</dependencies></dependencyManagement>",
+                    dependency("org.example:lib:2.0", ""),
+                    dependency("org.example:transitive:2.0", ""),
+                ),
+            )
+            .add(
+                "org.example:app:1.0",
+                &format!(
+                    "<dependencyManagement><dependencies>{}</dependencies></dependencyManagement>
+                     <dependencies>{}</dependencies>",
+                    dependency(
+                        "org.example:bom:1.0",
+                        "<type>pom</type><scope>import</scope>"
+                    ),
+                    dependency("org.example:lib", ""),
+                ),
+            )
+            .add(
+                "org.example:lib:2.0",
+                &format!(
+                    "<dependencies>{}</dependencies>",
+                    dependency("org.example:transitive:1.0", "")
+                ),
+            )
+            .add("org.example:transitive:1.0", "")
+            .add("org.example:transitive:2.0", "");
+        assert_eq!(
+            repository.resolve("org.example:app:1.0"),
+            ["app-1.0.jar", "lib-2.0.jar", "transitive-2.0.jar"]
+        );
+    }
+
+    #[test]
+    fn fails_for_missing_roots() {
+        let repository = TestRepository::new("missing");
+        let root = Coordinates::parse("org.example:app:1.0").unwrap();
+        let error = repository.repository.resolve(&[root]).unwrap_err();
+        assert!(
+            error.starts_with("org.example:app:1.0 isn't in "),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! The `[package.metadata.graal-bindgen]` table of a crate's `Cargo.toml`:
+//!
+//! ```toml
+//! [package.metadata.graal-bindgen]
+//! classpath = ["lib/mylib.jar", "java/build/classes"]
+//! maven = ["com.google.guava:guava:32.1.2-jre"]
+//! vm-options = ["Xmx2g"]
+//! ```
+//!
+//! Classpath entries are relative to the directory containing `Cargo.toml`.  Maven coordinates are
+//! resolved, with their dependencies, against `maven-repository`, which defaults to
+//! `~/.m2/repository`.
+
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::Value;
+
+use crate::lli;
+use crate::maven::{Coordinates, Repository};
+
+pub struct Metadata {
+    /// The classpath entries, then the files of the Maven artifacts.
+    pub classpath: Vec<PathBuf>,
+    /// The local Maven repository, if there is one.
+    pub maven_repository: Option<Repository>,
+    /// Options for the JVM, passed to `lli` as `--vm.OPTION`.
+    pub vm_options: Vec<String>,
+}
+
+impl Metadata {
+    /// Reads the metadata of the package whose manifest is `manifest_path`.
+    pub fn load(manifest_path: &Path) -> Result<Metadata, String> {
+        let output = Command::new(cargo())
+            .args(["metadata", "--no-deps", "--format-version", "1"])
+            .arg("--manifest-path")
+            .arg(manifest_path)
+            .output()
+            .map_err(|e| format!("couldn't run cargo metadata: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "cargo metadata failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let metadata: Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("couldn't parse cargo metadata: {}", e))?;
+        let package = metadata["packages"]
+            .as_array()
+            .and_then(|packages| {
+                packages.iter().find(|package| {
+                    package["manifest_path"].as_str().map(Path::new) == Some(manifest_path)
+                })
+            })
+            .ok_or_else(|| format!("cargo metadata didn't list {}", manifest_path.display()))?;
+
+        let table = &package["metadata"]["graal-bindgen"];
+        let root = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+        let mut classpath: Vec<PathBuf> = strings(table, "classpath")?
+            .into_iter()
+            .map(|entry| root.join(entry))
+            .collect();
+        let maven = strings(table, "maven")?
+            .iter()
+            .map(|coordinates| Coordinates::parse(coordinates))
+            .collect::<Result<Vec<_>, _>>()?;
+        let maven_repository = match &table["maven-repository"] {
+            Value::Null => Repository::default_root().map(Repository::new),
+            Value::String(path) => Some(Repository::new(root.join(path))),
+            _ => {
+                return Err(
+                    "package.metadata.graal-bindgen.maven-repository must be a string".to_owned(),
+                )
+            }
+        };
+        if !maven.is_empty() {
+            let repository = maven_repository
+                .as_ref()
+                .ok_or("there's no home directory to find ~/.m2/repository in")?;
+            classpath.extend(repository.resolve(&maven)?);
+        }
+        Ok(Metadata {
+            classpath,
+            maven_repository,
+            vm_options: strings(table, "vm-options")?,
+        })
+    }
+
+    /// The classpath joined with the platform's separator, or `None` if it's empty.
+    pub fn classpath(&self) -> Result<Option<OsString>, String> {
+        if self.classpath.is_empty() {
+            return Ok(None);
+        }
+        env::join_paths(&self.classpath)
+            .map(Some)
+            .map_err(|e| format!("invalid classpath entry: {}", e))
+    }
+
+    /// The options that make `lli` use this classpath and these JVM options.
+    pub fn lli_options(&self) -> Result<Vec<String>, String> {
+        let mut options = Vec::new();
+        if let Some(classpath) = self.classpath()? {
+            options.push(format!("--vm.cp={}", classpath.to_string_lossy()));
+        }
+        options.extend(self.vm_options.iter().map(|option| lli::vm_option(option)));
+        Ok(options)
+    }
+}
+
+/// Finds the `Cargo.toml` cargo would use, given the `--manifest-path` passed to `cargo graal`.
+pub fn locate_manifest(manifest_path: Option<&Path>) -> Result<PathBuf, String> {
+    let mut command = Command::new(cargo());
+    command.arg("locate-project");
+    if let Some(manifest_path) = manifest_path {
+        command.arg("--manifest-path").arg(manifest_path);
+    }
+    let output = command
+        .output()
+        .map_err(|e| format!("couldn't run cargo locate-project: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_owned());
+    }
+    let location: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("couldn't parse cargo locate-project: {}", e))?;
+    location["root"]
+        .as_str()
+        .map(PathBuf::from)
+        .ok_or_else(|| "cargo locate-project didn't print a manifest".to_owned())
+}
+
+pub fn cargo() -> OsString {
+    env::var_os("CARGO").unwrap_or_else(|| "cargo".into())
+}
+
+/// The array of strings at `table[key]`, which may be missing.
+fn strings(table: &Value, key: &str) -> Result<Vec<String>, String> {
+    match &table[key] {
+        Value::Null => Ok(Vec::new()),
+        Value::Array(values) => values
+            .iter()
+            .map(|value| {
+                value.as_str().map(str::to_owned).ok_or_else(|| {
+                    format!(
+                        "package.metadata.graal-bindgen.{} must be an array of strings",
+                        key
+                    )
+                })
+            })
+            .collect(),
+        _ => Err(format!(
+            "package.metadata.graal-bindgen.{} must be an array of strings",
+            key
+        )),
+    }
+}
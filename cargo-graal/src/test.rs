@@ -0,0 +1,423 @@
+//! Runs the `#[graal_test]` tests of a crate's bitcode under `lli`, and reports the results like
+//! `cargo test`.
+//!
+//! The entry point generated by `graal_test_main!` marks the start and result of each test on
+//! standard output.  If `lli` dies in the middle of a test, which happens when a Java exception
+//! isn't caught, that test fails and `lli` is restarted to run the rest.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Instant;
+
+use crate::lli::Lli;
+
+/// Must match `testing::MARKER` in graal-bindgen.
+const MARKER: &str = "#graal-test#";
+
+pub const USAGE: &str =
+    "[FILTER]... [--exact] [--ignored] [--include-ignored] [--list] [--nocapture]";
+
+struct Options<'a> {
+    lli: &'a Lli,
+    bitcode: &'a Path,
+    filters: Vec<String>,
+    exact: bool,
+    ignored: bool,
+    include_ignored: bool,
+    list: bool,
+    nocapture: bool,
+}
+
+enum Outcome {
+    Ok,
+    Failed(String),
+    Ignored,
+}
+
+impl<'a> Options<'a> {
+    fn parse(lli: &'a Lli, bitcode: &'a Path, args: &[String]) -> Result<Options<'a>, String> {
+        let mut filters = Vec::new();
+        let (mut exact, mut ignored, mut include_ignored, mut list, mut nocapture) =
+            (false, false, false, false, false);
+        for arg in args {
+            match arg.as_str() {
+                "--exact" => exact = true,
+                "--ignored" => ignored = true,
+                "--include-ignored" => include_ignored = true,
+                "--list" => list = true,
+                "--nocapture" => nocapture = true,
+                _ if arg.starts_with("--") => {
+                    return Err(format!("unknown test option {}, expected {}", arg, USAGE))
+                }
+                _ => filters.push(arg.clone()),
+            }
+        }
+        Ok(Options {
+            lli,
+            bitcode,
+            filters,
+            exact,
+            ignored,
+            include_ignored,
+            list,
+            nocapture,
+        })
+    }
+
+    fn lli(&self) -> Command {
+        self.lli.command(self.bitcode)
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.filters.is_empty()
+            || self.filters.iter().any(|filter| {
+                if self.exact {
+                    name == filter
+                } else {
+                    name.contains(filter.as_str())
+                }
+            })
+    }
+}
+
+fn list_tests(options: &Options) -> Result<Vec<String>, String> {
+    let output = options
+        .lli()
+        .arg("--list")
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| format!("couldn't run {}: {}", options.lli.path.display(), e))?;
+    if !output.status.success() {
+        return Err(format!("listing tests failed: {}", output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_suffix(": test"))
+        .map(str::to_owned)
+        .collect())
+}
+
+/// Runs `names` in one `lli` process, stopping early if it dies.
+fn run_batch(
+    options: &Options,
+    names: &[String],
+    results: &mut Vec<(String, Outcome)>,
+) -> Result<(), String> {
+    let mut command = options.lli();
+    if options.ignored {
+        command.arg("--ignored");
+    }
+    if options.include_ignored {
+        command.arg("--include-ignored");
+    }
+    let mut child = command
+        .args(names)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("couldn't run {}: {}", options.lli.path.display(), e))?;
+
+    let mut stderr = child.stderr.take().unwrap();
+    let stderr = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stderr.read_to_string(&mut output);
+        output
+    });
+
+    let out = &mut io::stdout();
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    let interrupted = read_batch(stdout, options.nocapture, out, results)?;
+    let status = child.wait().map_err(|e| e.to_string())?;
+    let stderr = stderr.join().unwrap_or_default();
+    finish_batch(interrupted, &status.to_string(), &stderr, out, results).map_err(|e| e.to_string())
+}
+
+/// Reads what one `lli` process printed, and reports each test it finished.  Returns the test
+/// that was running when the output ended, and what it had printed, if `lli` died in the middle
+/// of one.
+fn read_batch(
+    stdout: impl BufRead,
+    nocapture: bool,
+    out: &mut dyn Write,
+    results: &mut Vec<(String, Outcome)>,
+) -> Result<Option<(String, String)>, String> {
+    let mut current: Option<String> = None;
+    let mut captured = String::new();
+    for line in stdout.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        let mut marker = match line.strip_prefix(MARKER) {
+            Some(rest) => rest.trim_start().splitn(2, ' '),
+            None => {
+                if current.is_none() || nocapture {
+                    writeln!(out, "{}", line).map_err(|e| e.to_string())?;
+                }
+                captured.push_str(&line);
+                captured.push('\n');
+                continue;
+            }
+        };
+        let (status, name) = (
+            marker.next().unwrap_or(""),
+            marker.next().unwrap_or("").to_owned(),
+        );
+        let outcome = match status {
+            "start" => {
+                current = Some(name);
+                captured.clear();
+                continue;
+            }
+            "ok" => Outcome::Ok,
+            "failed" => Outcome::Failed(std::mem::take(&mut captured)),
+            "ignored" => Outcome::Ignored,
+            _ => return Err(format!("unexpected line from test binary: {}", line)),
+        };
+        current = None;
+        report(&name, &outcome, out).map_err(|e| e.to_string())?;
+        results.push((name, outcome));
+    }
+    Ok(current.map(|name| (name, captured)))
+}
+
+/// Fails the test `lli` died in the middle of, if it did, with its output, how `lli` exited and
+/// what it printed to standard error.  Otherwise standard error is passed through.
+fn finish_batch(
+    interrupted: Option<(String, String)>,
+    exit_status: &str,
+    stderr: &str,
+    out: &mut dyn Write,
+    results: &mut Vec<(String, Outcome)>,
+) -> io::Result<()> {
+    match interrupted {
+        Some((name, mut captured)) => {
+            captured.push_str(&format!(
+                "lli exited with {} in the middle of the test\n{}",
+                exit_status, stderr
+            ));
+            let outcome = Outcome::Failed(captured);
+            report(&name, &outcome, out)?;
+            results.push((name, outcome));
+        }
+        None if !stderr.is_empty() => eprint!("{}", stderr),
+        None => {}
+    }
+    Ok(())
+}
+
+fn report(name: &str, outcome: &Outcome, out: &mut dyn Write) -> io::Result<()> {
+    let status = match outcome {
+        Outcome::Ok => "ok",
+        Outcome::Failed(_) => "FAILED",
+        Outcome::Ignored => "ignored",
+    };
+    writeln!(out, "test {} ... {}", name, status)
+}
+
+/// Prints the output of the failed tests and the counts, like `cargo test`, and returns the exit
+/// code:  0 if every test passed, and 101 if any failed.
+fn summarize(
+    results: &[(String, Outcome)],
+    filtered_out: usize,
+    seconds: f64,
+    out: &mut dyn Write,
+) -> io::Result<i32> {
+    let failures: Vec<&(String, Outcome)> = results
+        .iter()
+        .filter(|(_, outcome)| matches!(outcome, Outcome::Failed(_)))
+        .collect();
+    if !failures.is_empty() {
+        writeln!(out, "\nfailures:\n")?;
+        for (name, outcome) in &failures {
+            if let Outcome::Failed(output) = outcome {
+                writeln!(out, "---- {} stdout ----\n{}", name, output)?;
+            }
+        }
+        writeln!(out, "\nfailures:")?;
+        for (name, _) in &failures {
+            writeln!(out, "    {}", name)?;
+        }
+    }
+    let count = |f: fn(&Outcome) -> bool| results.iter().filter(|(_, outcome)| f(outcome)).count();
+    writeln!(
+        out,
+        "\ntest result: {}. {} passed; {} failed; {} ignored; 0 measured; {} filtered out; finished in {:.2}s\n",
+        if failures.is_empty() { "ok" } else { "FAILED" },
+        count(|outcome| matches!(outcome, Outcome::Ok)),
+        failures.len(),
+        count(|outcome| matches!(outcome, Outcome::Ignored)),
+        filtered_out,
+        seconds
+    )?;
+    Ok(if failures.is_empty() { 0 } else { 101 })
+}
+
+/// Runs the tests in `bitcode`, selected by the test options in `args`, and returns the exit
+/// code.
+pub fn run(lli: &Lli, bitcode: &Path, args: &[String]) -> Result<i32, String> {
+    let options = &Options::parse(lli, bitcode, args)?;
+    let tests = list_tests(options)?;
+    let selected: Vec<String> = tests
+        .iter()
+        .filter(|name| options.matches(name))
+        .cloned()
+        .collect();
+    let filtered_out = tests.len() - selected.len();
+
+    if options.list {
+        for name in &selected {
+            println!("{}: test", name);
+        }
+        println!("\n{} tests, 0 benchmarks", selected.len());
+        return Ok(0);
+    }
+
+    let start = Instant::now();
+    println!(
+        "\nrunning {} test{}",
+        selected.len(),
+        if selected.len() == 1 { "" } else { "s" }
+    );
+    let mut results = Vec::new();
+    let mut remaining = selected;
+    while !remaining.is_empty() {
+        let before = results.len();
+        run_batch(options, &remaining, &mut results)?;
+        if results.len() == before {
+            return Err("lli exited without running any tests".to_owned());
+        }
+        remaining.retain(|name| !results.iter().any(|(done, _)| done == name));
+    }
+
+    summarize(
+        &results,
+        filtered_out,
+        start.elapsed().as_secs_f64(),
+        &mut io::stdout(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads captured `lli` output as one batch that exited with `exit_status`, and returns what
+    /// the runner printed, the results and the exit code.
+    fn run_output(stdout: &str, exit_status: &str) -> (String, Vec<(String, Outcome)>, i32) {
+        let mut out = Vec::new();
+        let mut results = Vec::new();
+        let interrupted = read_batch(stdout.as_bytes(), false, &mut out, &mut results).unwrap();
+        finish_batch(interrupted, exit_status, "", &mut out, &mut results).unwrap();
+        let code = summarize(&results, 1, 0.0, &mut out).unwrap();
+        (String::from_utf8(out).unwrap(), results, code)
+    }
+
+    #[test]
+    fn reports_passing_tests() {
+        let stdout = "\
+#graal-test# start tests::passes
+#graal-test# ok tests::passes
+#graal-test# ignored tests::ignored
+";
+        let (out, results, code) = run_output(stdout, "exit status: 0");
+        assert_eq!(
+            out,
+            "test tests::passes ... ok\n\
+             test tests::ignored ... ignored\n\
+             \ntest result: ok. 1 passed; 0 failed; 1 ignored; 0 measured; 1 filtered out; finished in 0.00s\n\n"
+        );
+        assert_eq!(results.len(), 2);
+        assert_eq!(code, 0);
+    }
+
+    #[test]
+    fn reports_failures_and_panics_with_their_output() {
+        let stdout = "\
+#graal-test# start tests::returns_err
+printed by the test
+#graal-test# failed tests::returns_err
+#graal-test# start tests::panics
+thread 'main' panicked at src/lib.rs:3:5:
+assertion failed
+#graal-test# failed tests::panics
+#graal-test# start tests::passes
+#graal-test# ok tests::passes
+";
+        let (out, results, code) = run_output(stdout, "exit status: 101");
+        assert!(out.starts_with(
+            "test tests::returns_err ... FAILED\n\
+             test tests::panics ... FAILED\n\
+             test tests::passes ... ok\n\
+             \nfailures:\n\n\
+             ---- tests::returns_err stdout ----\nprinted by the test\n\n\
+             ---- tests::panics stdout ----\n\
+             thread 'main' panicked at src/lib.rs:3:5:\nassertion failed\n\n\
+             \nfailures:\n    tests::returns_err\n    tests::panics\n"
+        ));
+        assert!(out.ends_with(
+            "\ntest result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 1 filtered out; finished in 0.00s\n\n"
+        ));
+        assert_eq!(results.len(), 3);
+        assert_eq!(code, 101);
+    }
+
+    #[test]
+    fn fails_the_test_lli_dies_in() {
+        let stdout = "\
+output before the tests
+#graal-test# start tests::passes
+#graal-test# ok tests::passes
+#graal-test# start tests::throws
+calling Java
+";
+        let mut out = Vec::new();
+        let mut results = Vec::new();
+        let interrupted = read_batch(stdout.as_bytes(), false, &mut out, &mut results).unwrap();
+        assert_eq!(
+            interrupted,
+            Some(("tests::throws".to_owned(), "calling Java\n".to_owned()))
+        );
+        finish_batch(
+            interrupted,
+            "exit status: 1",
+            "java.lang.IllegalStateException\n",
+            &mut out,
+            &mut results,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "output before the tests\n\
+             test tests::passes ... ok\n\
+             test tests::throws ... FAILED\n"
+        );
+        match &results[1] {
+            (name, Outcome::Failed(output)) => {
+                assert_eq!(name, "tests::throws");
+                assert_eq!(
+                    output,
+                    "calling Java\nlli exited with exit status: 1 in the middle of the test\n\
+                     java.lang.IllegalStateException\n"
+                );
+            }
+            _ => panic!("the test lli died in didn't fail"),
+        }
+        assert_eq!(summarize(&results, 0, 0.0, &mut Vec::new()).unwrap(), 101);
+    }
+
+    #[test]
+    fn rejects_unknown_markers() {
+        let error = read_batch(
+            "#graal-test# skipped tests::a\n".as_bytes(),
+            false,
+            &mut Vec::new(),
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            "unexpected line from test binary: #graal-test# skipped tests::a"
+        );
+    }
+}
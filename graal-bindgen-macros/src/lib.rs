@@ -2,12 +2,21 @@ use parse::Parser;
 use quote::{ToTokens, TokenStreamExt, format_ident, quote};
 use syn::{
     braced,
+    ext::IdentExt,
     parenthesized,
     parse::{self, Parse, ParseStream},
     punctuated::Punctuated,
     Ident, Token, Type,
 };
 
+mod names;
+
+use names::{to_snake_case, KEYWORDS, UNESCAPABLE_KEYWORDS};
+
+mod kw {
+    syn::custom_keyword!(implements);
+}
+
 macro_rules! punctuated_to_string {
     ($punctuated: expr, $separator: expr) => {
         $punctuated
@@ -63,6 +72,34 @@ impl JavaType {
     fn to_type(&self) -> Option<Type> {
         syn::parse(self.to_token_stream().into()).ok()
     }
+
+    /// The type in snake case, without its path or type arguments, for naming overloads, like
+    /// `object` for `Object` or `e_array` for `E[]`.
+    fn name_suffix(&self) -> String {
+        let name = match &self.ty {
+            Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.unraw().to_string()),
+            _ => None,
+        };
+        // Other types, like `*mut Value`, are named by their words.
+        let name = name.unwrap_or_else(|| {
+            let tokens = self.ty.to_token_stream().to_string();
+            let words: Vec<&str> = tokens
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .filter(|word| !word.is_empty())
+                .collect();
+            words.join("_")
+        });
+        let mut suffix = to_snake_case(&name).to_lowercase();
+        if suffix == "jchar" {
+            // Java's `char`, which `jtypes` renames so it doesn't shadow Rust's.
+            suffix = "char".to_owned();
+        }
+        if self.array {
+            suffix + "_array"
+        } else {
+            suffix
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -75,23 +112,27 @@ impl Parse for JavaTypedDeclaration {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         Ok(Self {
             ty: input.parse()?,
-            name: input.parse()?,
+            name: Ident::parse_any(input)?,
         })
     }
 }
 
 impl JavaTypedDeclaration {
-    fn to_rust_type_annotation(self) -> proc_macro2::TokenStream {
+    fn to_rust_type_annotation(self, snake_case: bool) -> syn::Result<proc_macro2::TokenStream> {
         let JavaTypedDeclaration { ty, name } = self;
+        let name = rust_name(&name, snake_case)?;
         let tokens = quote! {
             #name: #ty
         };
-        tokens
+        Ok(tokens)
     }
 }
 
 #[derive(Debug)]
 struct JavaFunctionStub {
+    attrs: Vec<syn::Attribute>,
+    fallible: bool,
+    mutating: bool,
     return_type: JavaType,
     rust_name: Ident,
     java_name: Option<Ident>,
@@ -102,15 +143,92 @@ struct JavaFunctionStub {
 impl Parse for JavaFunctionStub {
     fn parse(arg: ParseStream) -> syn::Result<Self> {
         let content;
+        let attrs = arg.call(syn::Attribute::parse_outer)?;
+        let (mut fallible, mut mutating) = (false, false);
+        loop {
+            if arg.parse::<Option<Token![try]>>()?.is_some() {
+                fallible = true;
+            } else if arg.parse::<Option<Token![mut]>>()?.is_some() {
+                mutating = true;
+            } else {
+                break;
+            }
+        }
         Ok(JavaFunctionStub {
+            attrs,
+            fallible,
+            mutating,
             return_type: arg.parse()?,
-            rust_name: arg.parse()?,
-            java_name: arg.parse().ok(),
+            rust_name: Ident::parse_any(arg)?,
+            java_name: Ident::parse_any(arg).ok(),
             bracket_token: parenthesized!(content in arg),
             args: content.parse_terminated(JavaTypedDeclaration::parse)?,
         })
     }
 }
+
+impl JavaFunctionStub {
+    fn java_name(&self) -> Ident {
+        self.java_name.as_ref().unwrap_or(&self.rust_name).unraw()
+    }
+
+    /// The name of the generated method.  A name given before the Java name is used as is, and
+    /// otherwise it's the Java name, in snake case if the class is `#[snake_case]`.  Overloads
+    /// without one get the types of their parameters appended, so `remove(int index)` and
+    /// `remove(Object o)` become `remove_int` and `remove_object`.  Keywords are escaped as raw
+    /// identifiers either way.
+    fn rust_name(&self, naming: &Naming) -> syn::Result<Ident> {
+        if self.java_name.is_some() {
+            return rust_name(&self.rust_name, false);
+        }
+        let java_name = self.java_name();
+        if !naming.overloaded.contains(&java_name.to_string()) || self.args.is_empty() {
+            return rust_name(&java_name, naming.snake_case);
+        }
+        let mut name = if naming.snake_case {
+            to_snake_case(&java_name.to_string())
+        } else {
+            java_name.to_string()
+        };
+        for arg in &self.args {
+            name.push('_');
+            name.push_str(&arg.ty.name_suffix());
+        }
+        rust_ident(&name, java_name.span())
+    }
+
+    /// `name(type, type)`, to tell overloads apart in errors.
+    fn signature(&self) -> String {
+        let types: Vec<String> = self
+            .args
+            .iter()
+            .map(|arg| arg.ty.to_token_stream().to_string().replace(' ', ""))
+            .collect();
+        format!("{}({})", self.java_name(), types.join(", "))
+    }
+}
+
+/// How the methods of a class are named.
+#[derive(Default)]
+struct Naming {
+    snake_case: bool,
+    /// The Java names of methods with more than one stub without a Rust name.
+    overloaded: std::collections::HashSet<String>,
+}
+
+impl Naming {
+    fn new<'a>(stubs: impl IntoIterator<Item = &'a JavaFunctionStub>, snake_case: bool) -> Naming {
+        let mut seen = std::collections::HashSet::new();
+        let mut overloaded = std::collections::HashSet::new();
+        for stub in stubs {
+            if stub.java_name.is_none() && !seen.insert(stub.java_name().to_string()) {
+                overloaded.insert(stub.java_name().to_string());
+            }
+        }
+        Naming { snake_case, overloaded }
+    }
+}
+
 #[derive(Debug)]
 struct AngleBracketGenerics {
     lbracket: Token![<],
@@ -138,6 +256,7 @@ impl ToTokens for AngleBracketGenerics {
 
 #[derive(Debug)]
 struct JavaQualifiedConstructorStub {
+    attrs: Vec<syn::Attribute>,
     fully_qualified_type_name: Punctuated<Ident, Token![.]>,
     rust_constructor_name: Ident,
     generics: Option<AngleBracketGenerics>,
@@ -151,6 +270,7 @@ impl Parse for JavaQualifiedConstructorStub {
         let content;
         let qualified_parser = Punctuated::<Ident, Token![.]>::parse_separated_nonempty;
         Ok(JavaQualifiedConstructorStub {
+            attrs: input.call(syn::Attribute::parse_outer)?,
             fully_qualified_type_name: qualified_parser(input)?,
             generics: input.parse::<AngleBracketGenerics>().ok(),
             rust_constructor_name: input.parse()?,
@@ -162,6 +282,7 @@ impl Parse for JavaQualifiedConstructorStub {
 
 #[derive(Debug)]
 struct JavaConstructorStub {
+    attrs: Vec<syn::Attribute>,
     rust_constructor_name: Ident,
 
     bracket_token: syn::token::Paren,
@@ -172,6 +293,7 @@ impl Parse for JavaConstructorStub {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let content;
         Ok(JavaConstructorStub {
+            attrs: input.call(syn::Attribute::parse_outer)?,
             rust_constructor_name: input.parse()?,
             bracket_token: parenthesized!(content in input),
             args: content.parse_terminated(JavaTypedDeclaration::parse)?,
@@ -187,7 +309,10 @@ enum JavaStub {
 
 impl Parse for JavaStub {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        if input.peek2(syn::token::Paren) {
+        // Look past the attributes, which both kinds of stub start with.
+        let fork = input.fork();
+        fork.call(syn::Attribute::parse_outer)?;
+        if fork.peek2(syn::token::Paren) {
             input.parse().map(Self::JavaConstructorStub)
         } else {
             input.parse().map(Self::JavaFunctionStub)
@@ -196,45 +321,134 @@ impl Parse for JavaStub {
 }
 #[derive(Debug)]
 struct Class {
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
     qualified_name: Punctuated<Ident, Token![.]>,
     generics: Option<AngleBracketGenerics>,
+    /// The interfaces after `implements`, of which only `Comparable` generates anything.
+    interfaces: Vec<JavaInterface>,
 
     bracket_token: syn::token::Brace,
     stubs: Punctuated<JavaStub, Token![;]>,
 }
 
+/// `java.lang.Comparable<String>`, whose type arguments are left out.
+#[derive(Debug)]
+struct JavaInterface {
+    qualified_name: Punctuated<Ident, Token![.]>,
+}
+
+impl Parse for JavaInterface {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let qualified_name = Punctuated::<Ident, Token![.]>::parse_separated_nonempty(input)?;
+        if input.peek(Token![<]) {
+            input.parse::<AngleBracketGenerics>()?;
+        }
+        Ok(Self { qualified_name })
+    }
+}
+
+impl JavaInterface {
+    fn is_comparable(&self) -> bool {
+        let name = punctuated_to_string!(self.qualified_name, ".");
+        name == "Comparable" || name == "java.lang.Comparable"
+    }
+}
+
 impl Parse for Class {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let content;
         let qualified_parser = Punctuated::<Ident, Token![.]>::parse_separated_nonempty;
         Ok(Self {
+            attrs: input.call(syn::Attribute::parse_outer)?,
+            // Classes are public unless they say otherwise.
+            vis: if input.peek(Token![pub]) {
+                input.parse()?
+            } else {
+                syn::parse_quote!(pub)
+            },
             qualified_name: qualified_parser(input)?,
             generics: input.parse::<AngleBracketGenerics>().ok(),
+            interfaces: if input.parse::<Option<kw::implements>>()?.is_some() {
+                Punctuated::<JavaInterface, Token![,]>::parse_separated_nonempty(input)?
+                    .into_iter()
+                    .collect()
+            } else {
+                vec![]
+            },
             bracket_token: braced!(content in input),
             stubs: content.parse_terminated(JavaStub::parse)?,
         })
     }
 }
 
+#[derive(Debug)]
+struct IncludePolyglot {
+    language: syn::LitStr,
+    path: syn::LitStr,
+}
+
+impl Parse for IncludePolyglot {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            language: input.parse()?,
+            path: {
+                input.parse::<Token![,]>()?;
+                input.parse()?
+            },
+        })
+    }
+}
+
+/// The Rust name for a Java name, converted to snake case if `snake_case` is set, and escaped as a
+/// raw identifier if it's a keyword.
+fn rust_name(java_name: &Ident, snake_case: bool) -> syn::Result<Ident> {
+    let java_name = java_name.unraw();
+    if snake_case {
+        rust_ident(&to_snake_case(&java_name.to_string()), java_name.span())
+    } else {
+        rust_ident(&java_name.to_string(), java_name.span())
+    }
+}
+
+/// An identifier for `name`, escaped as a raw identifier if it's a keyword.
+fn rust_ident(name: &str, span: proc_macro2::Span) -> syn::Result<Ident> {
+    if UNESCAPABLE_KEYWORDS.contains(&name) {
+        Err(syn::Error::new(
+            span,
+            format!("`{}` can't be a Rust name, even a raw one, so the stub needs another Rust name", name),
+        ))
+    } else if KEYWORDS.contains(&name) {
+        let mut raw: Ident = syn::parse_str(&format!("r#{}", name))?;
+        raw.set_span(span);
+        Ok(raw)
+    } else {
+        Ok(Ident::new(name, span))
+    }
+}
+
 fn parse_java_args(
     args: Punctuated<JavaTypedDeclaration, Token![,]>,
-) -> (Vec<proc_macro2::TokenStream>, Vec<proc_macro2::TokenStream>) {
-    let arg_names: Vec<proc_macro2::TokenStream> = args
+    snake_case: bool,
+) -> syn::Result<(Vec<proc_macro2::TokenStream>, Vec<proc_macro2::TokenStream>)> {
+    let arg_names = args
         .iter()
-        .map(|decl| decl.name.to_token_stream())
-        .collect();
+        .map(|decl| rust_name(&decl.name, snake_case).map(|name| name.to_token_stream()))
+        .collect::<syn::Result<Vec<proc_macro2::TokenStream>>>()?;
 
     let args = args
         .into_iter()
-        .map(|java_declaration| java_declaration.to_rust_type_annotation())
-        .collect::<Vec<proc_macro2::TokenStream>>();
+        .map(|java_declaration| java_declaration.to_rust_type_annotation(snake_case))
+        .collect::<syn::Result<Vec<proc_macro2::TokenStream>>>()?;
 
-    (args, arg_names)
+    Ok((args, arg_names))
 }
 
-fn get_return_token(return_type: Type) -> proc_macro2::TokenStream {
+fn get_return_token(return_type: Type, fallible: bool) -> proc_macro2::TokenStream {
     let return_type = if return_type.to_token_stream().to_string() == "void" {
         syn::parse_str("()").unwrap()
+    } else if fallible {
+        syn::parse2(quote!(Result<#return_type, crate::polyglot::ConversionError>)).unwrap()
     } else {
         return_type
     };
@@ -246,17 +460,27 @@ fn get_return_token(return_type: Type) -> proc_macro2::TokenStream {
     .into_token_stream()
 }
 
-fn get_return_and_conversion_prefix(return_type: &JavaType) -> proc_macro2::TokenStream {
-    let return_type_string = return_type.to_token_stream().to_string();
-    let return_type_string = return_type_string.replace("<", "::<"); // When invoking functions, syntax is Type::<GenericArg>::method(), rather than Type<GenericArg>::method().
-    if return_type_string == "void" {
-        proc_macro2::TokenStream::new()
+/// Wraps the invocation of a method so that its return value is converted with `TryReceive`.
+/// Fallible stubs return the `Result`, and the rest panic if the value can't be converted.
+fn quote_return_conversion(
+    return_type: &JavaType,
+    java_name: &str,
+    fallible: bool,
+    invocation: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    // `invoke_method!` returns what the method returned, which is a polyglot value or null.
+    if return_type.to_token_stream().to_string() == "void" {
+        quote! { #invocation; }
+    } else if fallible {
+        quote! {
+            let value = #invocation;
+            unsafe { <#return_type as crate::polyglot::TryReceive>::try_from_polyglot_value(value) }
+        }
     } else {
-        syn::parse_str(&format!(
-            "return {}::from_polyglot_value",
-            return_type_string
-        ))
-        .unwrap()
+        quote! {
+            let value = #invocation;
+            unsafe { crate::polyglot::expect_receive::<#return_type>(value, #java_name) }
+        }
     }
 }
 
@@ -265,8 +489,10 @@ fn quote_constructor_stub(
     fully_qualified_type_name: &Punctuated<Ident, Token![.]>,
     generics: &Option<AngleBracketGenerics>,
     stub: JavaConstructorStub,
-) -> proc_macro2::TokenStream {
+    snake_case: bool,
+) -> syn::Result<proc_macro2::TokenStream> {
     let JavaConstructorStub {
+        attrs,
         rust_constructor_name,
         args,
         ..
@@ -277,7 +503,7 @@ fn quote_constructor_stub(
         .expect("Could not parse type name.");
     let fully_qualified_type_name = punctuated_to_string!(fully_qualified_type_name, ".");
 
-    let (args, arg_names) = parse_java_args(args);
+    let (args, arg_names) = parse_java_args(args, snake_case)?;
     let name_lit = syn::LitStr::new(&fully_qualified_type_name, proc_macro2::Span::call_site());
 
     let generics_and_turbofish = if let Some(generics) = &generics {
@@ -290,20 +516,23 @@ fn quote_constructor_stub(
         None
     };
 
-    quote! {
+    Ok(quote! {
+        #(#attrs)*
         pub fn #rust_constructor_name (#(#args),*) -> #rust_type_name #generics {
             let polyglot_type = crate::polyglot::java_type(#name_lit);
-            #rust_type_name #generics_and_turbofish ::from_polyglot_value(crate::new_instance!(polyglot_type #(,#arg_names)*))
+            let instance = crate::new_instance!(polyglot_type #(,#arg_names)*);
+            unsafe { #rust_type_name #generics_and_turbofish ::from_polyglot_value(instance) }
         }
-    }
+    })
 }
 
 /// java.util.ArrayList new_with_length(int length);
 
 fn quote_qualified_constructor_stub(
     stub: JavaQualifiedConstructorStub,
-) -> proc_macro2::TokenStream {
+) -> syn::Result<proc_macro2::TokenStream> {
     let JavaQualifiedConstructorStub {
+        attrs,
         fully_qualified_type_name,
         rust_constructor_name,
         args,
@@ -317,7 +546,7 @@ fn quote_qualified_constructor_stub(
 
     let fully_qualified_type_name = punctuated_to_string!(fully_qualified_type_name, ".");
 
-    let (args, arg_names) = parse_java_args(args);
+    let (args, arg_names) = parse_java_args(args, false)?;
     let name_lit = syn::LitStr::new(&fully_qualified_type_name, proc_macro2::Span::call_site());
 
     let generics_and_turbofish = if let Some(generics) = &generics {
@@ -330,68 +559,43 @@ fn quote_qualified_constructor_stub(
         None //
     };
 
-    quote! {
+    Ok(quote! {
+        #(#attrs)*
         pub fn #rust_constructor_name (#(#args),*) -> #rust_type_name #generics {
             let polyglot_type = crate::polyglot::java_type(#name_lit);
-            #rust_type_name #generics_and_turbofish ::from_polyglot_value(crate::new_instance!(polyglot_type #(,#arg_names)*))
-        }
-    }
-}
-/**
- `[return_type] name [java_name]([args]);` \
- This function takes a JavaFunctionStub and generates the binding code for it. \
- The following JavaFunctionStub will generate a binding for
- `ArrayList#remove(int index)`, using remove_at as the rust name and `remove` as the java name. (some types shown as strings for clarity):
- ```rust
- JavaFunctionStub {
-    return_type: "int",
-    rust_name: "remove_at",
-    java_name: Some("remove"),
-    bracket_token: "()",
-    args: ["int index"]
- }
-```
- The generated code will look like this:
- ```rust
- pub fn remove_at(&self, index: int) -> E {
-    return E::from_polyglot_value({
-        unsafe {
-            crate::polyglot::internal::polyglot_invoke(
-                self.ptr,
-                crate::polyglot::internal::make_cstring("remove").as_ptr(),
-                crate::polyglot::internal::expect_variadic(index),
-            )
+            let instance = crate::new_instance!(polyglot_type #(,#arg_names)*);
+            unsafe { #rust_type_name #generics_and_turbofish ::from_polyglot_value(instance) }
         }
-    });
+    })
 }
-```
-If `java_name` is `None`, it will be assumed to be the same as the provided `rust_name`.
-The main purpose of `java_name` is to rename overloaded Java functions, since Rust does not support overloading.
-*/
-fn quote_function_stub(stub: JavaFunctionStub) -> proc_macro2::TokenStream {
+/// Generates a method for a stub, the way `java_method!` documents.
+fn quote_function_stub(stub: JavaFunctionStub, naming: &Naming) -> syn::Result<proc_macro2::TokenStream> {
+    let rust_name = stub.rust_name(naming)?;
+    // If no java name was provided, we just assume the java name is the same as the rust function name
+    let java_name = stub.java_name().to_string();
     let JavaFunctionStub {
+        attrs,
+        fallible,
+        mutating,
         return_type,
-        rust_name,
-        java_name,
         args,
         ..
     } = stub;
 
-    // If no java name was provided, we just assume the java name is the same as the rust function name
-    let java_name = java_name
-        .map(|x| x.to_string())
-        .unwrap_or(rust_name.to_string());
+    let (args, arg_names) = parse_java_args(args, naming.snake_case)?;
 
-    let (args, arg_names) = parse_java_args(args);
+    let invocation = quote!(crate::invoke_method!(self.ptr, #java_name #(,#arg_names)*));
+    let body = quote_return_conversion(&return_type, &java_name, fallible, invocation);
+    let return_token = get_return_token(return_type.to_type().unwrap(), fallible);
 
-    let conversion_method = get_return_and_conversion_prefix(&return_type);
-    let return_token = get_return_token(return_type.to_type().unwrap());
+    let receiver = if mutating { quote!(&mut self) } else { quote!(&self) };
 
-    quote::quote! {
-        pub fn #rust_name (&self, #(#args),*) #return_token {
-            #conversion_method (crate::invoke_method!(self.ptr, #java_name #(,#arg_names)*)) ;
+    Ok(quote::quote! {
+        #(#attrs)*
+        pub fn #rust_name (#receiver, #(#args),*) #return_token {
+            #body
         }
-    }
+    })
 }
 
 #[proc_macro]
@@ -401,7 +605,7 @@ pub fn java_constructor(input: proc_macro::TokenStream) -> proc_macro::TokenStre
     let mut output = proc_macro2::TokenStream::new();
 
     for stub in stubs {
-        let constructor_token = quote_qualified_constructor_stub(stub);
+        let constructor_token = quote_qualified_constructor_stub(stub).unwrap_or_else(|e| e.to_compile_error());
         //        println!("{}", constructor_token.to_string());
         constructor_token.to_tokens(&mut output);
     }
@@ -409,7 +613,7 @@ pub fn java_constructor(input: proc_macro::TokenStream) -> proc_macro::TokenStre
 }
 
 /**
- `[return_type] rust_name [java_name]([args]);` \
+ `[try] [mut] [return_type] rust_name [java_name]([args]);` \
  This function takes a JavaFunctionStub and generates the binding code for it. \
  The following JavaFunctionStub will generate a binding for
  `ArrayList#remove(int index)`, using remove_at as the rust name and `remove` as the java name. (some types shown as strings for clarity):
@@ -419,41 +623,178 @@ pub fn java_constructor(input: proc_macro::TokenStream) -> proc_macro::TokenStre
  The generated code will look like this:
  ```rust
  pub fn remove_at(&self, index: int) -> E {
-    return E::from_polyglot_value({
-        unsafe {
-            crate::polyglot::internal::polyglot_invoke(
-                self.ptr,
-                crate::polyglot::internal::make_cstring("remove").as_ptr(),
-                crate::polyglot::internal::expect_variadic(index),
-            )
-        }
-    });
+    let value = unsafe {
+        crate::polyglot::polyglot_invoke(
+            self.ptr,
+            crate::polyglot::make_cstr("remove").as_ptr(),
+            crate::polyglot::expect_variadic(index),
+        )
+    };
+    unsafe { crate::polyglot::expect_receive::<E>(value, "remove") }
 }
 ```
 If `java_name` is `None`, it will be assumed to be the same as the provided `rust_name`.
 The main purpose of `java_name` is to rename overloaded Java functions, since Rust does not support overloading.
+
+The return value is converted with `TryReceive`, and the method panics if it can't be converted.
+Prefixing the stub with `try` (or putting `#[fallible]` on its class) makes it return
+`Result<E, ConversionError>` instead.
+
+Methods take `&self` unless the stub is prefixed with `mut`, in which case they take `&mut self`.
+Stubs for methods that modify the object should be marked `mut`, so that the borrow checker can
+tell them apart from methods that only read it.
 */
 #[proc_macro]
 pub fn java_method(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let parser = Punctuated::<JavaFunctionStub, Token![;]>::parse_terminated;
     let stubs = parser.parse(input).expect("Could not parse stubs");
-    let mut output = proc_macro2::TokenStream::new();
+    let naming = Naming::new(&stubs, false);
+    let stubs: Punctuated<JavaStub, Token![;]> = stubs.into_iter().map(JavaStub::JavaFunctionStub).collect();
+    let (colliding, name_errors) = check_rust_names(&stubs, &naming);
+    let mut output = name_errors.map(|e| e.to_compile_error()).unwrap_or_default();
 
-    for stub in stubs {
-        let function_token = quote_function_stub(stub);
+    for (i, stub) in stubs.into_iter().enumerate() {
+        let stub = match stub {
+            JavaStub::JavaFunctionStub(stub) if !colliding.contains(&i) => stub,
+            _ => continue,
+        };
+        let function_token = quote_function_stub(stub, &naming).unwrap_or_else(|e| e.to_compile_error());
         //        println!("{}", function_token.to_string());
         function_token.to_tokens(&mut output);
     }
     output.into()
 }
 
-/// Generates bindings for a Java class, using method and constructor stubs provided in the body.
+/// Reports stubs that would generate methods with the same name, which snake case and overloads
+/// make easier to do by accident (`toArray` and `to_array`, or `foo(a.List l)` and `foo(b.List l)`).
+/// Returns the indices of the stubs to leave out, so the errors aren't followed by rustc's own
+/// about duplicate definitions.
+fn check_rust_names(
+    stubs: &Punctuated<JavaStub, Token![;]>,
+    naming: &Naming,
+) -> (std::collections::HashSet<usize>, Option<syn::Error>) {
+    let mut colliding = std::collections::HashSet::new();
+    // The description of the stub each name is taken by, and its Java name.
+    let mut names: std::collections::HashMap<String, (String, Option<Ident>)> = std::collections::HashMap::new();
+    names.insert("clone_ref".to_owned(), ("clone_ref".to_owned(), None));
+    let mut errors: Option<syn::Error> = None;
+    for (i, stub) in stubs.iter().enumerate() {
+        let (rust_name, java_name, description) = match stub {
+            JavaStub::JavaConstructorStub(stub) => {
+                let name = &stub.rust_constructor_name;
+                (name.clone(), None, format!("the constructor `{}`", name))
+            }
+            JavaStub::JavaFunctionStub(stub) => {
+                let rust_name = match stub.rust_name(naming) {
+                    Ok(rust_name) => rust_name,
+                    // Reported when the stub is generated.
+                    Err(_) => continue,
+                };
+                let description = if naming.overloaded.contains(&stub.java_name().to_string()) {
+                    format!("`{}`", stub.signature())
+                } else {
+                    format!("`{}`", stub.java_name())
+                };
+                (rust_name, Some(stub.java_name()), description)
+            }
+        };
+        let key = rust_name.unraw().to_string();
+        if let Some((other, other_java_name)) = names.get(&key) {
+            let mut message = if other == "clone_ref" {
+                format!("{} is named `clone_ref` in Rust, which every class! type already has", description)
+            } else if java_name.is_some() && *other_java_name == java_name {
+                format!("the overloads {} and {} are ambiguous in Rust, since both are named `{}`", other, description, key)
+            } else {
+                format!("{} is named `{}` in Rust, like {}", description, key, other)
+            };
+            if let Some(java_name) = &java_name {
+                message += &format!(";  give it another Rust name, like `{}_2 {}(...)`", key, java_name);
+            }
+            colliding.insert(i);
+            let error = syn::Error::new(rust_name.span(), message);
+            match &mut errors {
+                Some(errors) => errors.combine(error),
+                None => errors = Some(error),
+            }
+        } else {
+            names.insert(key, (description, java_name));
+        }
+    }
+    (colliding, errors)
+}
+
+/**
+ Generates bindings for a Java class, using method and constructor stubs provided in the body.
+ `#[snake_case]` before the class name converts the Java names of methods and parameters to snake
+ case, so `void ensureCapacity(int minCapacity)` becomes `ensure_capacity(&self, min_capacity: int)`:
+ ```java
+ class! [#[snake_case] java.util.ArrayList<E> {
+     E[] toArray();
+ }];
+ ```
+ `#[fallible]` makes every method return a `Result` instead of panicking when the return value
+ can't be converted, as if each stub were prefixed with `try`.
+ Doc comments and other attributes before the class name or a stub are put on the struct or
+ method, and a visibility before the class name (`pub(crate) java.util.ArrayList<E>`) is used for
+ the struct instead of `pub`.
+
+ Names given before the Java name are used as is.  Names that are Rust keywords, like `type`, are
+ escaped as raw identifiers.  Overloads without a Rust name get the types of their parameters
+ appended to it, so `E remove(int index)` and `boolean remove(Object o)` become `remove_int` and
+ `remove_object` (an overload without parameters keeps the plain name).  Two stubs ending up with
+ the same Rust name is an error.
+*/
 #[proc_macro]
 pub fn class(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let class = syn::parse_macro_input!(input as Class);
     let mut stubs = vec![];
 
+    // `#[snake_case]` and `#[fallible]` are for `class!`, and the rest are for the struct.  `#[cfg]`s
+    // go on the impls too, so they aren't left implementing a struct that isn't there.
+    let (mut snake_case, mut fallible) = (false, false);
+    let mut attrs = vec![];
+    let mut impl_attrs = vec![];
+    for attr in class.attrs {
+        let option = if attr.path.is_ident("snake_case") {
+            Some((&mut snake_case, "#[snake_case] takes no arguments"))
+        } else if attr.path.is_ident("fallible") {
+            Some((&mut fallible, "#[fallible] takes no arguments"))
+        } else {
+            None
+        };
+        if let Some((option, message)) = option {
+            if !attr.tokens.is_empty() {
+                return syn::Error::new_spanned(attr, message).to_compile_error().into();
+            }
+            *option = true;
+        } else {
+            if attr.path.is_ident("cfg") {
+                impl_attrs.push(attr.clone());
+            } else if attr.path.is_ident("deprecated") {
+                impl_attrs.push(syn::parse_quote!(#[allow(deprecated)]));
+            }
+            attrs.push(attr);
+        }
+    }
+    let vis = &class.vis;
+    let comparable = class.interfaces.iter().any(JavaInterface::is_comparable);
+    let naming = Naming::new(
+        class.stubs.iter().filter_map(|stub| match stub {
+            JavaStub::JavaFunctionStub(stub) => Some(stub),
+            JavaStub::JavaConstructorStub(_) => None,
+        }),
+        snake_case,
+    );
+    let (colliding, name_errors) = check_rust_names(&class.stubs, &naming);
+    let name_errors = name_errors.map(|e| e.to_compile_error());
+
     let rust_name = class.qualified_name.last().unwrap();
+    let class_name = class
+        .qualified_name
+        .iter()
+        .map(|segment| segment.unraw().to_string())
+        .collect::<Vec<_>>()
+        .join(".");
 
     let mut passable_generics: Vec<Type> = vec![]; // Add new generic types so we can constrain the value of our desired generic types to be Pass + Receive
     let generics = class.generics.map(|generics| {
@@ -473,7 +814,7 @@ pub fn class(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             stream.append_all(
                 quote! {
                     #ty_passable: crate::polyglot::Passable,
-                    #ty: crate::polyglot::Pass<#ty_passable> + crate::polyglot::Receive,
+                    #ty: crate::polyglot::Pass<#ty_passable> + crate::polyglot::TryReceive,
                 }
                 .to_token_stream(),
             );
@@ -505,32 +846,67 @@ pub fn class(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         }
     }
 
-    for stub in class.stubs {
-        match stub {
-            JavaStub::JavaConstructorStub(stub) => stubs.push(quote_constructor_stub(
-                &class.qualified_name,
-                &generics,
-                stub,
-            )),
-            JavaStub::JavaFunctionStub(stub) => stubs.push(quote_function_stub(stub)),
+    for (i, stub) in class.stubs.into_iter().enumerate() {
+        if colliding.contains(&i) {
+            continue;
         }
+        let stub = match stub {
+            JavaStub::JavaConstructorStub(stub) => {
+                quote_constructor_stub(&class.qualified_name, &generics, stub, snake_case)
+            }
+            JavaStub::JavaFunctionStub(mut stub) => {
+                stub.fallible |= fallible;
+                quote_function_stub(stub, &naming)
+            }
+        };
+        stubs.push(stub.unwrap_or_else(|e| e.to_compile_error()));
     }
 
+    let comparable_impls = if comparable {
+        Some(quote! {
+            #(#impl_attrs)*
+            impl#generics PartialOrd for #rust_name #generics where #generic_bounds {
+                fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+
+            #(#impl_attrs)*
+            impl#generics Ord for #rust_name #generics where #generic_bounds {
+                fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+                    crate::polyglot::object::compare_to(self, other)
+                }
+            }
+        })
+    } else {
+        None
+    };
+
     let result = quote! {
-        #[derive(Clone, Copy)]
-        pub struct #rust_name #generics where #generic_bounds
+        #name_errors
+
+        #(#attrs)*
+        #vis struct #rust_name #generics where #generic_bounds
         {
             ptr: *mut Value,
             #(#phantom_field_declarations),*
         }
 
+        #(#impl_attrs)*
         impl#generics #rust_name #generics where #generic_bounds {
+            /// Creates another reference to the same Java object, which is read-only since `self` is
+            /// borrowed immutably.  This doesn't call `clone()`.
+            pub fn clone_ref(&self) -> crate::polyglot::ReadOnly<Self> {
+                unsafe { crate::polyglot::Receive::from_polyglot_value(self.ptr) }
+            }
+
             #(#stubs)*
         }
 
+        #(#impl_attrs)*
         unsafe impl#generics crate::polyglot::Receive for #rust_name #generics where #generic_bounds
         {
-            fn from_polyglot_value(value: *mut Value) -> Self {
+            unsafe fn from_polyglot_value(value: *mut Value) -> Self {
                 Self {
                     ptr: value,
                     #(#phantom_field_initializations),*
@@ -538,11 +914,312 @@ pub fn class(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             }
         }
 
+        #(#impl_attrs)*
+        unsafe impl#generics crate::polyglot::TryReceive for #rust_name #generics where #generic_bounds
+        {
+            unsafe fn try_from_polyglot_value(value: *mut Value) -> Result<Self, crate::polyglot::ConversionError> {
+                crate::polyglot::object::check_reference(value, #class_name)?;
+                Ok(<Self as crate::polyglot::Receive>::from_polyglot_value(value))
+            }
+        }
+
+        #(#impl_attrs)*
         unsafe impl#generics crate::polyglot::Pass<*mut Value> for #rust_name #generics where #generic_bounds {
             fn pass(&self) -> *mut Value {
                 self.ptr
             }
         }
+
+        #(#impl_attrs)*
+        impl#generics crate::polyglot::JavaClass for #rust_name #generics where #generic_bounds {
+            const CLASS_NAME: &'static str = #class_name;
+        }
+
+        #(#impl_attrs)*
+        impl#generics core::fmt::Display for #rust_name #generics where #generic_bounds {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str(&crate::polyglot::object::to_string(self))
+            }
+        }
+
+        #(#impl_attrs)*
+        impl#generics core::fmt::Debug for #rust_name #generics where #generic_bounds {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                crate::polyglot::object::debug(self, f)
+            }
+        }
+
+        #(#impl_attrs)*
+        impl#generics PartialEq for #rust_name #generics where #generic_bounds {
+            fn eq(&self, other: &Self) -> bool {
+                crate::polyglot::object::equals(self, other)
+            }
+        }
+
+        #(#impl_attrs)*
+        impl#generics Eq for #rust_name #generics where #generic_bounds {}
+
+        #(#impl_attrs)*
+        impl#generics core::hash::Hash for #rust_name #generics where #generic_bounds {
+            fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+                core::hash::Hash::hash(&crate::polyglot::object::hash_code(self), state)
+            }
+        }
+
+        #comparable_impls
+    };
+
+    result.into()
+}
+
+/**
+ `include_polyglot!("language", "path")` \
+ Embeds a source file of another language in the binary, and evaluates it with `polyglot::eval`
+ the first time the expression is evaluated on each thread.  The result of that evaluation
+ (usually an object of exports) is kept in a thread-local `Handle`, and returned as a
+ `ReadOnly<PolyglotValue>` every time after that on the same thread.
+ Panics if the evaluation fails or returns null.
+ ```rust
+ let helpers = include_polyglot!("js", "scripts/helpers.js");
+ let greeting = helpers.invoke("greet", (name,));
+ ```
+ The path is relative to the directory containing the crate's `Cargo.toml`, rather than the
+ directory `lli` runs in.
+*/
+#[proc_macro]
+pub fn include_polyglot(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let IncludePolyglot { language, path, .. } = syn::parse_macro_input!(input as IncludePolyglot);
+
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".into());
+    let full_path = std::path::Path::new(&manifest_dir).join(path.value());
+    let error = match std::fs::read_to_string(&full_path) {
+        Ok(source) if source.contains('\0') => Some(format!("{} contains a zero byte", full_path.display())),
+        Ok(_) => None,
+        Err(e) => Some(format!("couldn't read {}: {}", full_path.display(), e)),
+    };
+    if let Some(error) = error {
+        return syn::Error::new(path.span(), error).to_compile_error().into();
+    }
+    if language.value().contains('\0') {
+        return syn::Error::new(language.span(), "language ID contains a zero byte")
+            .to_compile_error()
+            .into();
+    }
+
+    let full_path = syn::LitStr::new(&full_path.to_string_lossy(), path.span());
+
+    let result = quote! {{
+        thread_local! {
+            // The result is kept in a native handle, since a managed pointer isn't valid in a
+            // static, and per thread, since a context like JavaScript's (or the mock's) can only
+            // be used from the thread that entered it.
+            static EVALUATED: std::cell::OnceCell<crate::polyglot::Handle<crate::polyglot::PolyglotValue>> =
+                std::cell::OnceCell::new();
+        }
+        EVALUATED.with(|evaluated| {
+            evaluated
+                .get_or_init(|| {
+                    let language = crate::polyglot::Language::Custom(#language);
+                    let value = crate::polyglot::eval(language, include_str!(#full_path))
+                        .unwrap_or_else(|e| panic!("couldn't evaluate {}: {}", #full_path, e));
+                    crate::polyglot::Handle::new(&value)
+                })
+                .get()
+        })
+    }};
+
+    result.into()
+}
+
+/// The function `#[graal_test]` generates next to a test, which `graal_test_main!` calls to
+/// collect it.
+fn test_collector(test: &Ident) -> Ident {
+    format_ident!("__graal_test_{}", test.unraw())
+}
+
+/// Reads `#[should_panic]` or `#[should_panic(expected = "...")]`.
+fn parse_should_panic(attr: &syn::Attribute) -> syn::Result<proc_macro2::TokenStream> {
+    match attr.parse_meta()? {
+        syn::Meta::Path(_) => Ok(quote!(crate::testing::ShouldPanic::Yes)),
+        syn::Meta::List(list) => match list.nested.iter().next() {
+            Some(syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                lit: syn::Lit::Str(expected),
+                ..
+            }))) if path.is_ident("expected") && list.nested.len() == 1 => {
+                Ok(quote!(crate::testing::ShouldPanic::WithMessage(#expected)))
+            }
+            _ => Err(syn::Error::new_spanned(&list, "expected `should_panic(expected = \"...\")`")),
+        },
+        meta => Err(syn::Error::new_spanned(meta, "expected `should_panic(expected = \"...\")`")),
+    }
+}
+
+/**
+ `#[graal_test]` \
+ Marks a function as a test to be run under `lli`, where it can use Java and other languages.
+ Tests can also be marked `#[ignore]` and `#[should_panic]`, and can return a `Result`.
+ ```rust
+ #[graal_test]
+ fn list_add() {
+     let mut list = ArrayList::new();
+     list.add(1);
+     assert_eq!(list.size(), 1);
+ }
+ ```
+ Tests are only compiled with the `graal-test` feature, and are run by the entry point that
+ `graal_test_main!` generates, which must list every test.  A test that isn't listed fails the
+ build with an error that it's never used.
+*/
+#[proc_macro_attribute]
+pub fn graal_test(args: proc_macro::TokenStream, input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    if !args.is_empty() {
+        return syn::Error::new(proc_macro2::Span::call_site(), "#[graal_test] takes no arguments")
+            .to_compile_error()
+            .into();
+    }
+    let mut function = syn::parse_macro_input!(input as syn::ItemFn);
+    let signature = &function.sig;
+    if !signature.inputs.is_empty() || signature.asyncness.is_some() || !signature.generics.params.is_empty() {
+        return syn::Error::new_spanned(signature, "test functions can't take arguments, be generic or be async")
+            .to_compile_error()
+            .into();
+    }
+
+    let mut ignore = false;
+    let mut should_panic = quote!(crate::testing::ShouldPanic::No);
+    let mut errors = Vec::new();
+    function.attrs.retain(|attr| {
+        if attr.path.is_ident("ignore") {
+            ignore = true;
+            false
+        } else if attr.path.is_ident("should_panic") {
+            match parse_should_panic(attr) {
+                Ok(tokens) => should_panic = tokens,
+                Err(e) => errors.push(e.to_compile_error()),
+            }
+            false
+        } else {
+            true
+        }
+    });
+
+    let ident = &function.sig.ident;
+    let collector = test_collector(ident);
+
+    let result = quote! {
+        #(#errors)*
+
+        // The test is only called through the collector, which only `graal_test_main!` calls, so
+        // a test it doesn't list fails the build instead of being silently left out.
+        #[cfg(feature = "graal-test")]
+        #[deny(dead_code)]
+        #function
+
+        #[cfg(feature = "graal-test")]
+        #[doc(hidden)]
+        pub(crate) fn #collector() -> crate::testing::Test {
+            crate::testing::Test {
+                name: concat!(module_path!(), "::", stringify!(#ident)),
+                run: || crate::testing::TestResult::check(#ident()),
+                ignore: #ignore,
+                should_panic: #should_panic,
+            }
+        }
+    };
+
+    result.into()
+}
+
+/**
+ `graal_test_main!(path::to::test, ...);` \
+ Generates the entry point that runs the given `#[graal_test]` functions, when the crate is built
+ with the `graal-test` feature.  Tests are given by their paths from where the macro is used,
+ usually the end of `main.rs`, so the modules they're in have to be visible there.  The crate's
+ own `main` should be left out of `graal-test` builds.  `cargo graal test` runs the entry point
+ under `lli` and reports the results.
+ ```rust
+ graal_test_main!(tests::list_add, strings::tests::encode_utf16);
+ ```
+*/
+#[proc_macro]
+pub fn graal_test_main(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let parser = Punctuated::<syn::Path, Token![,]>::parse_terminated;
+    let tests = match parser.parse(input) {
+        Ok(tests) => tests,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let mut collectors = Vec::new();
+    for mut test in tests {
+        let last = test.segments.last_mut().unwrap();
+        if !last.arguments.is_empty() {
+            return syn::Error::new_spanned(test, "expected the path of a #[graal_test] function")
+                .to_compile_error()
+                .into();
+        }
+        last.ident = test_collector(&last.ident);
+        collectors.push(test);
+    }
+
+    let result = quote! {
+        #[cfg(feature = "graal-test")]
+        #[no_mangle]
+        extern "C" fn main(argc: i32, argv: *const *const std::os::raw::c_char) -> i32 {
+            let tests = vec![#(#collectors()),*];
+            crate::entry::run(|| crate::testing::run(tests, unsafe { crate::entry::args(argc, argv) }))
+        }
+    };
+
+    result.into()
+}
+
+/**
+ `#[graal_bindgen_macros::main]` \
+ Generates the C `main` that `lli` calls from a Rust `main` function.  The function can take the
+ program's arguments as a `Vec<String>`, and return `()`, an `i32` exit code, or a `Result`.
+ ```rust
+ #[graal_bindgen_macros::main]
+ fn main(args: Vec<String>) -> Result<(), String> {
+     let name = args.get(1).ok_or("usage: greet NAME")?;
+     println!("Hello, {}!", name);
+     Ok(())
+ }
+ ```
+ An `Err` is printed and exits with 1, and a panic exits with 101 instead of unwinding into
+ `lli`.  The crate needs `#![no_main]`, since the generated function is the entry point.
+*/
+#[proc_macro_attribute]
+pub fn main(args: proc_macro::TokenStream, input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    if !args.is_empty() {
+        return syn::Error::new(proc_macro2::Span::call_site(), "#[main] takes no arguments")
+            .to_compile_error()
+            .into();
+    }
+    let function = syn::parse_macro_input!(input as syn::ItemFn);
+    let signature = &function.sig;
+    if signature.inputs.len() > 1 || signature.asyncness.is_some() || !signature.generics.params.is_empty() {
+        return syn::Error::new_spanned(
+            signature,
+            "main can take at most one argument, the `Vec<String>` of program arguments, and can't be generic or async",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let ident = &signature.ident;
+    let call = if signature.inputs.is_empty() {
+        quote!(#ident())
+    } else {
+        quote!(#ident(unsafe { crate::entry::args(argc, argv) }))
+    };
+    let result = quote! {
+        #[cfg_attr(not(test), no_mangle)]
+        #[allow(unused_variables)]
+        extern "C" fn main(argc: i32, argv: *const *const std::os::raw::c_char) -> i32 {
+            #function
+
+            crate::entry::run(|| #call)
+        }
     };
 
     result.into()
@@ -0,0 +1,38 @@
+//! How `class!` turns Java names into Rust ones.  `cargo graal bindgen` includes this file too, so
+//! the bindings it generates name things the same way.
+
+/// Rust keywords, and words reserved for them, which have to be raw identifiers to be used as names.
+pub const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "trait", "true", "try", "type", "unsafe", "use", "where",
+    "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv", "typeof",
+    "unsized", "virtual", "yield",
+];
+
+/// Keywords that can't even be raw identifiers.
+pub const UNESCAPABLE_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+/// Converts a Java name to snake case the way rustc suggests, so `toArray` becomes `to_array` and
+/// `getURLPath` becomes `get_url_path`.
+pub fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let next_is_lowercase = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            let word_start = i > 0
+                && match chars[i - 1] {
+                    previous if previous.is_uppercase() => next_is_lowercase,
+                    previous => previous != '_',
+                };
+            if word_start {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
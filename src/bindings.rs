@@ -133,29 +133,6 @@ pub type __pid_t = crate::types::ctypes::c_int;
 pub struct __fsid_t {
     pub __val: [crate::types::ctypes::c_int; 2usize],
 }
-#[test]
-fn bindgen_test_layout___fsid_t() {
-    assert_eq!(
-        ::std::mem::size_of::<__fsid_t>(),
-        8usize,
-        concat!("Size of: ", stringify!(__fsid_t))
-    );
-    assert_eq!(
-        ::std::mem::align_of::<__fsid_t>(),
-        4usize,
-        concat!("Alignment of ", stringify!(__fsid_t))
-    );
-    assert_eq!(
-        unsafe { &(*(::std::ptr::null::<__fsid_t>())).__val as *const _ as usize },
-        0usize,
-        concat!(
-            "Offset of field: ",
-            stringify!(__fsid_t),
-            "::",
-            stringify!(__val)
-        )
-    );
-}
 pub type __clock_t = crate::types::ctypes::c_long;
 pub type __rlim_t = crate::types::ctypes::c_ulong;
 pub type __rlim64_t = crate::types::ctypes::c_ulong;
@@ -201,7 +178,7 @@ pub type uint_fast32_t = crate::types::ctypes::c_ulong;
 pub type uint_fast64_t = crate::types::ctypes::c_ulong;
 pub type intmax_t = __intmax_t;
 pub type uintmax_t = __uintmax_t;
-extern "C" {
+extern "C-unwind" {
     #[doc = " Import a value from the global polyglot"]
     #[doc = " {@link org::graalvm::polyglot::Context::getPolyglotBindings bindings}."]
     #[doc = ""]
@@ -211,7 +188,7 @@ extern "C" {
         name: *const crate::types::ctypes::c_char,
     ) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Export a value to the global polyglot"]
     #[doc = " {@link org::graalvm::polyglot::Context::getPolyglotBindings bindings}."]
     #[doc = ""]
@@ -222,7 +199,7 @@ extern "C" {
         value: *mut crate::types::ctypes::c_void,
     );
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Evaluate a source of another language."]
     #[doc = ""]
     #[doc = " @param id the language identifier"]
@@ -234,7 +211,7 @@ extern "C" {
         code: *const crate::types::ctypes::c_char,
     ) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Evaluate a file containing source of another language."]
     #[doc = ""]
     #[doc = " The filename argument can be absolute or relative to the current working"]
@@ -249,7 +226,7 @@ extern "C" {
         filename: *const crate::types::ctypes::c_char,
     ) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Access a Java class via host interop."]
     #[doc = ""]
     #[doc = " @param classname the name of the Java class"]
@@ -258,7 +235,7 @@ extern "C" {
         classname: *const crate::types::ctypes::c_char,
     ) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Access an argument of the current function."]
     #[doc = ""]
     #[doc = " This function can be used to access arguments of the current function by"]
@@ -266,20 +243,20 @@ extern "C" {
     #[doc = " their exact type."]
     pub fn polyglot_get_arg(i: crate::types::ctypes::c_int) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Get the number of arguments passed to the current function."]
     #[doc = ""]
     #[doc = " This function can be used to get the number of passed arguments, regular and"]
     #[doc = " varargs, without using the va_list API."]
     pub fn polyglot_get_arg_count() -> crate::types::ctypes::c_int;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a pointer points to a polyglot value."]
     #[doc = ""]
     #[doc = " @see org::graalvm::polyglot::Value"]
     pub fn polyglot_is_value(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot value is NULL."]
     #[doc = ""]
     #[doc = " Note that this is different from a native NULL pointer. A native pointer can"]
@@ -292,7 +269,7 @@ extern "C" {
     #[doc = " @see org::graalvm::polyglot::Value::isNull"]
     pub fn polyglot_is_null(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot value is a number."]
     #[doc = ""]
     #[doc = " Returns false for pointers that do not point to a polyglot value (see"]
@@ -301,7 +278,7 @@ extern "C" {
     #[doc = " @see org::graalvm::polyglot::Value::isNumber"]
     pub fn polyglot_is_number(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot value is a boolean."]
     #[doc = ""]
     #[doc = " Returns false for pointers that do not point to a polyglot value (see"]
@@ -312,7 +289,7 @@ extern "C" {
     #[doc = " @see org::graalvm::polyglot::Value::isBoolean"]
     pub fn polyglot_is_boolean(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot value is a string."]
     #[doc = ""]
     #[doc = " Returns false for pointers that do not point to a polyglot value (see"]
@@ -321,7 +298,7 @@ extern "C" {
     #[doc = " @see org::graalvm::polyglot::Value::isString"]
     pub fn polyglot_is_string(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot number can be losslessly converted to a signed"]
     #[doc = " 8-bit integer (int8_t)."]
     #[doc = ""]
@@ -329,7 +306,7 @@ extern "C" {
     #[doc = " {@link polyglot_is_number})."]
     pub fn polyglot_fits_in_i8(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot number can be losslessly converted to a signed"]
     #[doc = " 16-bit integer (int16_t)."]
     #[doc = ""]
@@ -337,7 +314,7 @@ extern "C" {
     #[doc = " {@link polyglot_is_number})."]
     pub fn polyglot_fits_in_i16(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot number can be losslessly converted to a signed"]
     #[doc = " 32-bit integer (int32_t)."]
     #[doc = ""]
@@ -345,7 +322,7 @@ extern "C" {
     #[doc = " {@link polyglot_is_number})."]
     pub fn polyglot_fits_in_i32(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot number can be losslessly converted to a signed"]
     #[doc = " 64-bit integer (int64_t)."]
     #[doc = ""]
@@ -353,7 +330,7 @@ extern "C" {
     #[doc = " {@link polyglot_is_number})."]
     pub fn polyglot_fits_in_i64(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot number can be losslessly converted to a single"]
     #[doc = " precision floating point number."]
     #[doc = ""]
@@ -361,7 +338,7 @@ extern "C" {
     #[doc = " {@link polyglot_is_number})."]
     pub fn polyglot_fits_in_float(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot number can be losslessly converted to a double"]
     #[doc = " precision floating point number."]
     #[doc = ""]
@@ -369,35 +346,35 @@ extern "C" {
     #[doc = " {@link polyglot_is_number})."]
     pub fn polyglot_fits_in_double(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Convert a polyglot number to a primitive int8_t value."]
     pub fn polyglot_as_i8(value: *const crate::types::ctypes::c_void) -> i8;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Convert a polyglot number to a primitive int16_t value."]
     pub fn polyglot_as_i16(value: *const crate::types::ctypes::c_void) -> i16;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Convert a polyglot number to a primitive int32_t value."]
     pub fn polyglot_as_i32(value: *const crate::types::ctypes::c_void) -> i32;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Convert a polyglot number to a primitive int64_t value."]
     pub fn polyglot_as_i64(value: *const crate::types::ctypes::c_void) -> i64;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Convert a polyglot number to a primitive float value."]
     pub fn polyglot_as_float(value: *const crate::types::ctypes::c_void) -> f32;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Convert a polyglot number to a primitive double value."]
     pub fn polyglot_as_double(value: *const crate::types::ctypes::c_void) -> f64;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Convert a polyglot boolean to a primitive bool value."]
     pub fn polyglot_as_boolean(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot value can be executed."]
     #[doc = ""]
     #[doc = " To execute a polyglot value, cast it to a function pointer type and call it."]
@@ -406,7 +383,7 @@ extern "C" {
     #[doc = " {@link polyglot_is_value})."]
     pub fn polyglot_can_execute(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Invoke an object oriented method on a polyglot value."]
     #[doc = ""]
     #[doc = " @param object the object containing the method"]
@@ -419,14 +396,14 @@ extern "C" {
         ...
     ) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot value can be instantiated."]
     #[doc = ""]
     #[doc = " Returns false for pointers that do not point to a polyglot value (see"]
     #[doc = " {@link polyglot_is_value})."]
     pub fn polyglot_can_instantiate(object: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Instantiate a polyglot value."]
     #[doc = ""]
     #[doc = " @param object the polyglot value that should be instantiated"]
@@ -437,14 +414,14 @@ extern "C" {
         ...
     ) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot value is an object with named members."]
     #[doc = ""]
     #[doc = " Returns false for pointers that do not point to a polyglot value (see"]
     #[doc = " {@link polyglot_is_value})."]
     pub fn polyglot_has_members(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot value contains a given named member."]
     #[doc = ""]
     #[doc = " @param object the polyglot value to test"]
@@ -455,7 +432,7 @@ extern "C" {
         name: *const crate::types::ctypes::c_char,
     ) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Read a named member from a polyglot object."]
     #[doc = ""]
     #[doc = " The result is also a polyglot value. Use the {@link unbox primitive conversion"]
@@ -469,7 +446,7 @@ extern "C" {
         name: *const crate::types::ctypes::c_char,
     ) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Put a named member into a polyglot object."]
     #[doc = ""]
     #[doc = " This varargs function has to be called with exactly 3 arguments. The type"]
@@ -485,7 +462,7 @@ extern "C" {
         ...
     );
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Remove a named member from a polyglot object."]
     #[doc = ""]
     #[doc = " @param object the polyglot value to modify"]
@@ -496,18 +473,18 @@ extern "C" {
         name: *const crate::types::ctypes::c_char,
     ) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Check whether a polyglot value has array elements."]
     #[doc = ""]
     #[doc = " Returns false for pointers that do not point to a polyglot value (see"]
     #[doc = " {@link polyglot_is_value})."]
     pub fn polyglot_has_array_elements(value: *const crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Get the size of the polyglot array."]
     pub fn polyglot_get_array_size(array: *const crate::types::ctypes::c_void) -> u64;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Read an array element from a polyglot array."]
     #[doc = ""]
     #[doc = " The result is also a polyglot value. Use the {@link unbox primitive conversion"]
@@ -521,7 +498,7 @@ extern "C" {
         idx: crate::types::ctypes::c_int,
     ) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Write an array element to a polyglot array."]
     #[doc = ""]
     #[doc = " This varargs function has to be called with exactly 3 arguments. The type"]
@@ -537,7 +514,7 @@ extern "C" {
         ...
     );
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Remove an array element from a polyglot array."]
     #[doc = ""]
     #[doc = " @param array the polyglot array to modify"]
@@ -548,13 +525,13 @@ extern "C" {
         idx: crate::types::ctypes::c_int,
     ) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Get the size of a polyglot string value."]
     #[doc = ""]
     #[doc = " @return the size of the string, in unicode characters"]
     pub fn polyglot_get_string_size(value: *const crate::types::ctypes::c_void) -> u64;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Convert a polyglot value to a C string."]
     #[doc = ""]
     #[doc = " The C string will be written to a caller-provided buffer. This function"]
@@ -578,7 +555,7 @@ extern "C" {
         charset: *const crate::types::ctypes::c_char,
     ) -> u64;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Convert a zero-terminated C string to a polyglot string."]
     #[doc = ""]
     #[doc = " The C string is expected to be terminated with a zero character. If the"]
@@ -592,7 +569,7 @@ extern "C" {
         charset: *const crate::types::ctypes::c_char,
     ) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Convert a C string with explicit size to a polyglot string."]
     #[doc = ""]
     #[doc = " This function reads exactly `len` bytes from `string`. Zero characters are"]
@@ -617,7 +594,7 @@ pub struct __polyglot_typeid {
 #[doc = ""]
 #[doc = " @see POLYGLOT_DECLARE_STRUCT"]
 pub type polyglot_typeid = *mut __polyglot_typeid;
-extern "C" {
+extern "C-unwind" {
     #[doc = " Declare an array type."]
     #[doc = ""]
     #[doc = " @param base the element type of the array"]
@@ -625,7 +602,7 @@ extern "C" {
     #[doc = " @return a new typeid referring to an array of base with length len"]
     pub fn polyglot_array_typeid(base: polyglot_typeid, len: u64) -> polyglot_typeid;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Converts a polyglot value to a dynamic struct or array pointer."]
     #[doc = ""]
     #[doc = " The typeid passed to this function must refer to a struct or array type."]
@@ -642,7 +619,7 @@ extern "C" {
         typeId: polyglot_typeid,
     ) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Create a polyglot value from a native pointer to a struct or array."]
     #[doc = ""]
     #[doc = " The typeid passed to this function must refer to a struct or array type."]
@@ -659,35 +636,35 @@ extern "C" {
         typeId: polyglot_typeid,
     ) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     #[doc = " Internal function. Do not use directly."]
     #[doc = ""]
     #[doc = " @see POLYGLOT_DECLARE_STRUCT"]
     #[doc = " @see POLYGLOT_DECLARE_TYPE"]
     pub fn __polyglot_as_typeid(ptr: *mut crate::types::ctypes::c_void) -> polyglot_typeid;
 }
-extern "C" {
+extern "C-unwind" {
     pub fn _graalvm_llvm_create_handle(
         managedObject: *mut crate::types::ctypes::c_void,
     ) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     pub fn _graalvm_llvm_resolve_handle(
         nativeHandle: *mut crate::types::ctypes::c_void,
     ) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     pub fn _graalvm_llvm_release_handle(nativeHandle: *mut crate::types::ctypes::c_void);
 }
-extern "C" {
+extern "C-unwind" {
     pub fn _graalvm_llvm_create_deref_handle(
         managedObject: *mut crate::types::ctypes::c_void,
     ) -> *mut crate::types::ctypes::c_void;
 }
-extern "C" {
+extern "C-unwind" {
     pub fn _graalvm_llvm_is_handle(nativeHandle: *mut crate::types::ctypes::c_void) -> bool;
 }
-extern "C" {
+extern "C-unwind" {
     pub fn _graalvm_llvm_points_to_handle_space(
         nativeHandle: *mut crate::types::ctypes::c_void,
     ) -> bool;
@@ -1,51 +1,39 @@
 use std::marker::PhantomData;
 
 use crate::types::jtypes::*;
-use graal_bindgen_macros::{class, java_constructor};
+use graal_bindgen_macros::class;
 
-use crate::polyglot::{Pass, Receive, Value};
+use crate::polyglot::{object, JavaClass, Receive, Value};
 
-pub struct Object {
-    ptr: *mut Value,
-}
+class! [java.lang.Object {}];
 
-unsafe impl Pass<*mut Value> for Object {
-    fn pass(&self) -> *mut Value {
-        self.ptr
-    }
-}
-unsafe impl Receive for Object {
-    fn from_polyglot_value(value: *mut Value) -> Self {
-        Self { ptr: value }
+impl Object {
+    /// Whether the object is an instance of `T`'s class, like Java's `instanceof`.  A null
+    /// reference isn't an instance of anything.
+    pub fn is_instance_of<T: JavaClass>(&self) -> bool {
+        object::is_instance(self, T::CLASS_NAME)
     }
-}
-
-pub struct String {
-    ptr: *mut Value,
-}
 
-unsafe impl Pass<*mut Value> for String {
-    fn pass(&self) -> *mut Value {
-        self.ptr
-    }
-}
-unsafe impl Receive for String {
-    fn from_polyglot_value(value: *mut Value) -> Self {
-        Self { ptr: value }
+    /// Converts the object to `T` if it's an instance of `T`'s class, or gives it back if it
+    /// isn't.  Type arguments can't be checked, since Java erases them.
+    pub fn downcast<T: JavaClass>(self) -> Result<T, Object> {
+        if self.is_instance_of::<T>() {
+            Ok(unsafe { T::from_polyglot_value(self.ptr) })
+        } else {
+            Err(self)
+        }
     }
 }
 
-impl String {
-    java_constructor! {
-        java.lang.String new();
-    }
-}
+class! [java.lang.String implements Comparable<String> {
+    new();
+}];
 
-class! [java.util.ArrayList<E> {
+class! [#[snake_case] java.util.ArrayList<E> {
     new_with_length(int initialCapacity);
     new();
-    void trimToSize();
-    void ensureCapacity(int minCapacity);
+    mut void trimToSize();
+    mut void ensureCapacity(int minCapacity);
     int size();
     boolean isEmpty();
     boolean contains(Object o);
@@ -54,16 +42,86 @@ class! [java.util.ArrayList<E> {
     Object clone();
     E[] toArray();
     E get(int index);
-    E set(int index, E element);
-    boolean add(E e);
-    void add_at add(int index, E element);
-    E remove_at remove(int index);
-    boolean remove_item remove(Object o);
-    void clear();
-   void removeRange(int fromIndex, int toIndex);
+    mut E set(int index, E element);
+    mut boolean add(E e);
+    mut void add_at add(int index, E element);
+    mut E remove_at remove(int index);
+    mut boolean remove_item remove(Object o);
+    mut void clear();
+    mut void removeRange(int fromIndex, int toIndex);
 }];
 
+/// Tests against a real JVM, which `cargo graal test` runs under `lli`.
+#[cfg(feature = "graal-test")]
+pub(crate) mod graal_tests {
+    use super::*;
+    use graal_bindgen_macros::graal_test;
 
+    #[graal_test]
+    fn array_list_add() {
+        let mut list = ArrayList::new();
+        list.add(1);
+        list.add_at(0, 2);
+        assert_eq!(list.size(), 2);
+        assert_eq!(list.get(0), 2);
+        assert_eq!(list.remove_at(1), 1);
+        assert_eq!(list.size(), 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polyglot::mock::MockValue;
+    use crate::polyglot::{Pass, PolyglotValue};
+
+    fn value(value: impl Into<MockValue>) -> PolyglotValue {
+        value.into().receive()
+    }
+
+    mod fallible {
+        use super::*;
 
+        class! [#[fallible] java.util.HashMap<K, V> {
+            new();
+            mut V put(K key, V value);
+            V get(K key);
+        }];
+    }
+
+    #[test]
+    fn receiving_a_class_checks_the_value() {
+        let error = value(MockValue::Null).receive::<ArrayList<i32, i32>>().unwrap_err();
+        assert_eq!(error.to_string(), "expected java.util.ArrayList, found null");
+        let error = value(3).receive::<ArrayList<i32, i32>>().unwrap_err();
+        assert_eq!(error.to_string(), "expected java.util.ArrayList, found number");
+        let list = value(MockValue::Null).receive::<Option<ArrayList<i32, i32>>>();
+        assert!(list.unwrap().is_none());
+        assert!(value(3).receive::<Object>().is_ok());
+        assert!(value("s").receive::<String>().is_ok());
+        assert!(value(3).receive::<String>().is_err());
+    }
 
+    #[test]
+    fn fallible_classes_return_results() {
+        let mut map = fallible::HashMap::<i32, i32, i32, i32>::new();
+        assert!(map.put(1, 10).is_err());
+        assert_eq!(map.put(1, 11), Ok(10));
+        assert_eq!(map.get(1), Ok(11));
+        let error = map.get(2).unwrap_err();
+        assert_eq!(error.to_string(), "expected i32, found null");
+    }
 
+    #[test]
+    fn debug_includes_the_class_name() {
+        let mut list = ArrayList::<i32, i32>::new();
+        list.add(1);
+        list.add(2);
+        assert_eq!(format!("{:?}", list), "java.util.ArrayList(\"[1, 2]\")");
+        assert_eq!(list.to_string(), "[1, 2]");
+        let string = value("s").receive::<String>().unwrap();
+        assert_eq!(format!("{:?}", string), "java.lang.String(\"s\")");
+        let null = unsafe { Object::from_polyglot_value(value(MockValue::Null).pass()) };
+        assert_eq!(format!("{:?}", null), "java.lang.Object(null)");
+    }
+}
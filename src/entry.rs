@@ -0,0 +1,92 @@
+//! Runtime support for `#[graal_bindgen_macros::main]`, which generates a C `main` for `lli` that
+//! calls `run`.
+
+use std::ffi::CStr;
+use std::fmt::Debug;
+use std::os::raw::c_char;
+use std::panic;
+
+/// The exit code of a program that panicked, as with a native Rust program.
+pub const PANIC_EXIT_CODE: i32 = 101;
+
+/// The return type of a `main` function:  `()`, an exit code, or a `Result` whose error is
+/// printed.
+pub trait MainResult {
+    fn exit_code(self) -> i32;
+}
+
+impl MainResult for () {
+    fn exit_code(self) -> i32 {
+        0
+    }
+}
+
+impl MainResult for i32 {
+    fn exit_code(self) -> i32 {
+        self
+    }
+}
+
+impl<T: MainResult, E: Debug> MainResult for Result<T, E> {
+    fn exit_code(self) -> i32 {
+        match self {
+            Ok(value) => value.exit_code(),
+            Err(e) => {
+                eprintln!("Error: {:?}", e);
+                1
+            }
+        }
+    }
+}
+
+/// Runs `main`, returning its exit code.  A panic is caught rather than unwinding into `lli`;  the
+/// panic hook has already printed it, so it only changes the exit code.
+pub fn run<R: MainResult>(main: impl FnOnce() -> R + panic::UnwindSafe) -> i32 {
+    match panic::catch_unwind(main) {
+        Ok(result) => result.exit_code(),
+        Err(_) => PANIC_EXIT_CODE,
+    }
+}
+
+/// Converts C's `argc` and `argv` to the program's arguments.  Like `std::env::args`, the first
+/// is the path of the program, and an argument that isn't valid UTF-8 panics, which `run` turns
+/// into exit code 101, rather than being replaced.
+///
+/// # Safety
+/// `argv` must point to `argc` valid zero-terminated strings.
+pub unsafe fn args(argc: i32, argv: *const *const c_char) -> Vec<String> {
+    (0..argc.max(0) as usize)
+        .map(|i| {
+            let arg = CStr::from_ptr(*argv.add(i));
+            match arg.to_str() {
+                Ok(arg) => arg.to_owned(),
+                Err(e) => panic!("argument {} is not valid UTF-8 ({}): {:?}", i, e, arg),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_the_arguments() {
+        let argv = [b"prog\0".as_ptr() as *const c_char, b"x\0".as_ptr() as *const c_char];
+        assert_eq!(unsafe { args(2, argv.as_ptr()) }, ["prog", "x"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "argument 1 is not valid UTF-8")]
+    fn rejects_invalid_arguments() {
+        let argv = [b"prog\0".as_ptr() as *const c_char, b"\xff\0".as_ptr() as *const c_char];
+        unsafe { args(2, argv.as_ptr()) };
+    }
+
+    #[test]
+    fn exits_with_the_result() {
+        assert_eq!(run(|| ()), 0);
+        assert_eq!(run(|| 3), 3);
+        assert_eq!(run(|| Err::<(), _>("failed")), 1);
+    }
+}
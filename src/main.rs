@@ -1,22 +1,27 @@
-#![no_main]
+#![cfg_attr(not(test), no_main)]
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
-#![allow(non_snake_case)]
 
 pub mod builtins;
+pub mod entry;
 pub mod polyglot;
+pub mod testing;
 pub mod types;
 
+#[cfg(not(feature = "graal-test"))]
 use builtins::*;
-#[no_mangle]
-pub extern "C" fn main() {
-    let list = ArrayList::new();
-    let list_in_list = ArrayList::new();
+use graal_bindgen_macros::graal_test_main;
+
+#[cfg(not(feature = "graal-test"))]
+#[graal_bindgen_macros::main]
+fn main() {
+    let mut list = ArrayList::new();
+    let mut list_in_list = ArrayList::new();
     for i in 0..100 {
         list_in_list.add(i);
     }
     list.add(list_in_list);
-    let array_from_list = list.get(0).toArray();
+    let array_from_list = list.get(0).to_array();
     for i in 0..100 {
         println!("{}", array_from_list.get(i).unwrap());
     }
@@ -32,3 +37,5 @@ pub extern "C" fn main() {
     //     println!("{}", slice_from_vec.get(i).unwrap());
     // }
 }
+
+graal_test_main!(builtins::graal_tests::array_list_add);
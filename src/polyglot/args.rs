@@ -0,0 +1,99 @@
+use std::os::raw::c_char;
+
+use super::strings::{self, StringError};
+use super::{polyglot_get_arg, polyglot_get_arg_count, PolyglotValue, Receive};
+
+/// An iterator over the arguments passed to a function, returned by `args`.
+pub struct Args {
+    values: std::vec::IntoIter<PolyglotValue>,
+}
+
+/// The arguments passed to the current function by the host or another language.
+///
+/// `polyglot_get_arg` reads the arguments of the function it's called from, so this is always
+/// inlined and must be called directly from the function whose arguments you want, usually the
+/// entry point.  `lli` calls `main` with C's `argc` and `argv`;  use `Args::from_c` for those.
+#[inline(always)]
+pub fn args() -> Args {
+    let count = unsafe { polyglot_get_arg_count() };
+    let mut values = Vec::with_capacity(count.max(0) as usize);
+    for i in 0..count {
+        values.push(unsafe { PolyglotValue::from_polyglot_value(polyglot_get_arg(i)) });
+    }
+    Args {
+        values: values.into_iter(),
+    }
+}
+
+impl Args {
+    /// Converts C's `argc` and `argv`, as `lli` passes them to `main`, to polyglot strings.  Like
+    /// `entry::args`, an argument that isn't valid UTF-8 panics rather than being replaced.
+    ///
+    /// # Safety
+    /// `argv` must point to `argc` valid zero-terminated strings.
+    pub unsafe fn from_c(argc: i32, argv: *const *const c_char) -> Args {
+        let values: Vec<PolyglotValue> = crate::entry::args(argc, argv)
+            .iter()
+            .map(|arg| strings::from_str(arg))
+            .collect();
+        Args {
+            values: values.into_iter(),
+        }
+    }
+
+    /// Converts each argument to a Rust `String`, failing for arguments that aren't strings.
+    pub fn strings(self) -> impl Iterator<Item = Result<String, StringError>> {
+        self.map(|arg| arg.to_rust_string())
+    }
+}
+
+impl Iterator for Args {
+    type Item = PolyglotValue;
+
+    fn next(&mut self) -> Option<PolyglotValue> {
+        self.values.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Args {}
+
+impl DoubleEndedIterator for Args {
+    fn next_back(&mut self) -> Option<PolyglotValue> {
+        self.values.next_back()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polyglot::mock::{self, MockValue};
+
+    #[test]
+    fn reads_the_polyglot_arguments() {
+        mock::set_args(vec![MockValue::string("-v"), MockValue::from(2)]);
+        let args = args();
+        assert_eq!(args.len(), 2);
+        let strings: Vec<_> = args.strings().collect();
+        assert_eq!(strings[0], Ok("-v".to_owned()));
+        assert_eq!(strings[1], Err(StringError::NotAString));
+    }
+
+    #[test]
+    fn converts_argc_and_argv() {
+        let argv = [b"prog\0".as_ptr() as *const c_char, b"x\0".as_ptr() as *const c_char];
+        let args = unsafe { Args::from_c(2, argv.as_ptr()) };
+        let strings: Result<Vec<_>, _> = args.rev().map(|arg| arg.to_rust_string()).collect();
+        assert_eq!(strings.unwrap(), ["x", "prog"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "argument 1 is not valid UTF-8")]
+    fn rejects_invalid_c_arguments() {
+        let argv = [b"prog\0".as_ptr() as *const c_char, b"\xff\0".as_ptr() as *const c_char];
+        unsafe { Args::from_c(2, argv.as_ptr()) };
+    }
+}
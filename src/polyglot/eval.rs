@@ -0,0 +1,193 @@
+use core::fmt;
+use std::ffi::{CString, NulError};
+use std::path::Path;
+
+use super::{
+    polyglot_eval, polyglot_eval_file, polyglot_is_null, ConversionError, TryReceive, Value,
+};
+
+/// A language installed in GraalVM, identified by its language ID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Language {
+    Js,
+    Python,
+    Ruby,
+    R,
+    Llvm,
+    Wasm,
+    /// Any other language, by its ID.
+    Custom(&'static str),
+}
+
+impl Language {
+    pub fn id(self) -> &'static str {
+        match self {
+            Language::Js => "js",
+            Language::Python => "python",
+            Language::Ruby => "ruby",
+            Language::R => "R",
+            Language::Llvm => "llvm",
+            Language::Wasm => "wasm",
+            Language::Custom(id) => id,
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+#[derive(Debug)]
+pub enum EvalError {
+    /// The language ID, source or file name contains a zero byte.
+    Nul(NulError),
+    /// The file name isn't valid UTF-8.
+    InvalidPath,
+    /// The evaluation returned null.
+    Null,
+    /// The result couldn't be converted to the requested type.
+    Conversion(ConversionError),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::Nul(e) => write!(f, "cannot pass string to polyglot: {}", e),
+            EvalError::InvalidPath => write!(f, "file name is not valid UTF-8"),
+            EvalError::Null => write!(f, "evaluation returned null"),
+            EvalError::Conversion(e) => write!(f, "cannot convert evaluation result: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EvalError::Nul(e) => Some(e),
+            EvalError::Conversion(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<NulError> for EvalError {
+    fn from(e: NulError) -> Self {
+        EvalError::Nul(e)
+    }
+}
+
+impl From<ConversionError> for EvalError {
+    fn from(e: ConversionError) -> Self {
+        EvalError::Conversion(e)
+    }
+}
+
+/// Evaluates `source` in another language and converts the result to `T`.
+/// ```rust
+/// let sum: i32 = polyglot::eval(Language::Js, "1 + 2").unwrap();
+/// ```
+pub fn eval<T: TryReceive>(language: Language, source: &str) -> Result<T, EvalError> {
+    let id = CString::new(language.id())?;
+    let source = CString::new(source)?;
+    receive_result(unsafe { polyglot_eval(id.as_ptr(), source.as_ptr()) })
+}
+
+/// Evaluates the file at `path` in another language and converts the result to `T`.
+/// Relative paths are resolved against the working directory of the GraalVM process.
+pub fn eval_file<T: TryReceive, P: AsRef<Path>>(
+    language: Language,
+    path: P,
+) -> Result<T, EvalError> {
+    let id = CString::new(language.id())?;
+    let path = path.as_ref().to_str().ok_or(EvalError::InvalidPath)?;
+    let path = CString::new(path)?;
+    receive_result(unsafe { polyglot_eval_file(id.as_ptr(), path.as_ptr()) })
+}
+
+fn receive_result<T: TryReceive>(value: *mut Value) -> Result<T, EvalError> {
+    if value.is_null() || unsafe { polyglot_is_null(value) } {
+        return Err(EvalError::Null);
+    }
+    Ok(unsafe { T::try_from_polyglot_value(value) }?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polyglot::mock::{self, MockValue};
+    use crate::polyglot::PolyglotValue;
+
+    #[test]
+    fn evaluates_source_in_the_requested_language() {
+        mock::on_eval(|id, source| MockValue::string(&format!("{}: {}", id, source)));
+        let result: PolyglotValue = eval(Language::Python, "1 + 2").unwrap();
+        assert_eq!(result.to_rust_string().unwrap(), "python: 1 + 2");
+        let result: PolyglotValue = eval(Language::Custom("lua"), "x").unwrap();
+        assert_eq!(result.to_rust_string().unwrap(), "lua: x");
+    }
+
+    #[test]
+    fn converts_the_result() {
+        mock::on_eval(|_, _| MockValue::from(3.5));
+        assert_eq!(eval::<f64>(Language::Js, "3.5").unwrap(), 3.5);
+        match eval::<i32>(Language::Js, "3.5") {
+            Err(EvalError::Conversion(e)) => assert_eq!(e.expected, "i32"),
+            result => panic!("expected a conversion error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn reports_null_results_and_zero_bytes() {
+        mock::on_eval(|_, _| MockValue::Null);
+        assert!(matches!(eval::<PolyglotValue>(Language::Js, "null"), Err(EvalError::Null)));
+        assert!(matches!(eval::<PolyglotValue>(Language::Js, "a\0b"), Err(EvalError::Nul(_))));
+    }
+
+    #[test]
+    fn evaluates_files() {
+        mock::on_eval_file(|id, path| MockValue::string(&format!("{} {}", id, path)));
+        let result: PolyglotValue = eval_file(Language::Ruby, "lib/rules.rb").unwrap();
+        assert_eq!(result.to_rust_string().unwrap(), "ruby lib/rules.rb");
+    }
+
+    #[test]
+    #[should_panic(expected = "use mock::on_eval")]
+    fn evaluating_without_an_evaluator_panics() {
+        let _ = eval::<PolyglotValue>(Language::Js, "1");
+    }
+
+    #[test]
+    fn include_polyglot_evaluates_the_file_once_per_thread() {
+        fn included() -> crate::polyglot::ReadOnly<PolyglotValue> {
+            graal_bindgen_macros::include_polyglot!("js", "Cargo.toml")
+        }
+        fn count_evaluations() -> std::rc::Rc<std::cell::Cell<usize>> {
+            let calls = std::rc::Rc::new(std::cell::Cell::new(0));
+            let counter = calls.clone();
+            mock::on_eval(move |id, source| {
+                counter.set(counter.get() + 1);
+                MockValue::string(&format!("{} {}", id, source.len()))
+            });
+            calls
+        }
+
+        let calls = count_evaluations();
+        let expected = format!("js {}", include_str!("../../Cargo.toml").len());
+        for _ in 0..2 {
+            assert_eq!(included().to_rust_string().unwrap(), expected);
+        }
+        assert_eq!(calls.get(), 1);
+
+        // The mock, like a JavaScript context, belongs to one thread, so another thread evaluates
+        // the file in its own.
+        let other_thread = std::thread::spawn(|| {
+            let calls = count_evaluations();
+            included().to_rust_string().unwrap();
+            calls.get()
+        });
+        assert_eq!(other_thread.join().unwrap(), 1);
+        assert_eq!(calls.get(), 1);
+    }
+}
@@ -0,0 +1,140 @@
+use core::fmt;
+
+use super::{
+    make_cstr, polyglot_export, polyglot_import, polyglot_is_null, ConversionError, Pass, Receive,
+    TryReceive, Value,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportError {
+    /// Nothing has been exported with this name.
+    Missing(String),
+    /// The value exported with this name couldn't be converted to the binding's type.
+    WrongType(String, ConversionError),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Missing(name) => write!(f, "polyglot binding `{}` is not defined", name),
+            ImportError::WrongType(name, e) => write!(f, "polyglot binding `{}`: {}", name, e),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+fn import_value(name: &str) -> Option<*mut Value> {
+    let value = unsafe { polyglot_import(make_cstr(name).as_ptr()) };
+    if value.is_null() || unsafe { polyglot_is_null(value) } {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// Imports a value from the polyglot bindings, or `None` if nothing was exported as `name`.
+pub fn import<T: Receive>(name: &str) -> Option<T> {
+    import_value(name).map(|value| unsafe { T::from_polyglot_value(value) })
+}
+
+/// Imports a value from the polyglot bindings, checking that it exists and has the right type.
+pub fn try_import<T: TryReceive>(name: &str) -> Result<T, ImportError> {
+    let value = import_value(name).ok_or_else(|| ImportError::Missing(name.to_owned()))?;
+    unsafe { T::try_from_polyglot_value(value) }.map_err(|e| ImportError::WrongType(name.to_owned(), e))
+}
+
+/// Exports a value to the polyglot bindings, where the host and other languages can import it.
+pub fn export<T: Pass<*mut Value>>(name: &str, value: &T) {
+    unsafe { polyglot_export(make_cstr(name).as_ptr(), value.pass()) }
+}
+
+/// Declares a struct of values imported from the polyglot bindings.  Each field is imported from
+/// the binding with the name after `=`, or with the field's name if there isn't one.
+/// ```rust
+/// bindings! {
+///     pub struct Host {
+///         config: PolyglotValue,
+///         max_retries: i32 = "maxRetries",
+///     }
+/// }
+///
+/// let host = Host::import().expect("host did not export its bindings");
+/// ```
+/// `import` checks every binding at once, so a missing or mistyped binding is reported at startup
+/// rather than when it's first used.
+#[macro_export]
+macro_rules! bindings {
+    (@name $field: ident) => { stringify!($field) };
+    (@name $field: ident $global: literal) => { $global };
+    (
+        $(#[$meta: meta])*
+        $vis: vis struct $name: ident {
+            $($field_vis: vis $field: ident: $ty: ty $(= $global: literal)?),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($field_vis $field: $ty),*
+        }
+
+        impl $name {
+            /// Imports every binding, failing on the first one that is missing or has the wrong type.
+            $vis fn import() -> Result<Self, $crate::polyglot::ImportError> {
+                Ok(Self {
+                    $($field: $crate::polyglot::try_import($crate::bindings!(@name $field $($global)?))?),*
+                })
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polyglot::mock::{self, MockValue};
+    use crate::polyglot::PolyglotValue;
+
+    #[test]
+    fn imports_what_was_exported() {
+        export("answer", &MockValue::from(42).receive::<PolyglotValue>());
+        assert_eq!(import::<i32>("answer"), Some(42));
+        assert!(matches!(mock::binding("answer"), Some(MockValue::Int(42))));
+        assert_eq!(import::<i32>("question"), None);
+    }
+
+    #[test]
+    fn try_import_reports_missing_and_mistyped_bindings() {
+        mock::bind("name", "config");
+        assert_eq!(
+            try_import::<i32>("missing"),
+            Err(ImportError::Missing("missing".to_owned()))
+        );
+        match try_import::<i32>("name") {
+            Err(ImportError::WrongType(name, e)) => {
+                assert_eq!(name, "name");
+                assert_eq!(e.expected, "i32");
+            }
+            result => panic!("expected a type error, got {:?}", result.map(|_| ())),
+        }
+    }
+
+    crate::bindings! {
+        struct Host {
+            retries: i32 = "maxRetries",
+            verbose: bool,
+        }
+    }
+
+    #[test]
+    fn bindings_imports_every_field() {
+        mock::bind("maxRetries", 3);
+        assert_eq!(
+            Host::import().map(|_| ()),
+            Err(ImportError::Missing("verbose".to_owned()))
+        );
+        mock::bind("verbose", true);
+        let host = Host::import().unwrap();
+        assert_eq!((host.retries, host.verbose), (3, true));
+    }
+}
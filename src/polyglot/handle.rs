@@ -0,0 +1,115 @@
+use core::marker::PhantomData;
+
+use super::{
+    _graalvm_llvm_create_deref_handle, _graalvm_llvm_create_handle, _graalvm_llvm_is_handle,
+    _graalvm_llvm_release_handle, _graalvm_llvm_resolve_handle, Pass, ReadOnly, Receive, Value,
+};
+
+/// A native handle to a managed polyglot value.
+///
+/// Managed values can't be stored in native memory, like a C struct or a `Box` that is passed
+/// through native code.  A handle is a native pointer that can be stored anywhere and resolved back
+/// to the value later.  The handle is released when it's dropped.
+pub struct Handle<T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    ptr: *mut Value,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Handle<T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    pub fn new(value: &T) -> Self {
+        Self {
+            ptr: unsafe { _graalvm_llvm_create_handle(value.pass()) },
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates a handle that native code can also dereference, with reads and writes through the
+    /// pointer going to the managed value.
+    pub fn new_deref(value: &T) -> Self {
+        Self {
+            ptr: unsafe { _graalvm_llvm_create_deref_handle(value.pass()) },
+            phantom: PhantomData,
+        }
+    }
+
+    /// Resolves the handle to the value it was created from.  The handle was created from a shared
+    /// reference, so the value can only be read through it.
+    pub fn get(&self) -> ReadOnly<T> {
+        unsafe { ReadOnly::from_polyglot_value(_graalvm_llvm_resolve_handle(self.ptr)) }
+    }
+
+    /// The native pointer of the handle.  It's only valid as long as the handle isn't dropped.
+    pub fn as_ptr(&self) -> *mut Value {
+        self.ptr
+    }
+
+    /// Gives up ownership of the handle without releasing it, so that it can be stored in native
+    /// memory.  Use `from_raw` to release it later.
+    pub fn into_raw(self) -> *mut Value {
+        let ptr = self.ptr;
+        core::mem::forget(self);
+        ptr
+    }
+
+    /// Takes ownership of a handle returned by `into_raw`.  Returns `None` if `ptr` isn't a handle.
+    ///
+    /// # Safety
+    /// `ptr` must have been created from a value of type `T`, and must not be owned by another
+    /// `Handle`, or it will be released twice.
+    pub unsafe fn from_raw(ptr: *mut Value) -> Option<Self> {
+        if _graalvm_llvm_is_handle(ptr) {
+            Some(Self {
+                ptr,
+                phantom: PhantomData,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Drop for Handle<T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    fn drop(&mut self) {
+        unsafe { _graalvm_llvm_release_handle(self.ptr) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polyglot::mock::MockValue;
+    use crate::polyglot::PolyglotValue;
+
+    #[test]
+    fn resolves_to_the_value() {
+        let value: PolyglotValue = MockValue::from(5).receive();
+        let handle = Handle::new(&value);
+        assert_eq!(handle.get().receive::<i32>(), Ok(5));
+        assert!(unsafe { _graalvm_llvm_is_handle(handle.as_ptr()) });
+    }
+
+    #[test]
+    fn releases_on_drop() {
+        let value: PolyglotValue = MockValue::from(5).receive();
+        let ptr = Handle::new_deref(&value).as_ptr();
+        assert!(!unsafe { _graalvm_llvm_is_handle(ptr) });
+    }
+
+    #[test]
+    fn survives_a_round_trip_through_native_memory() {
+        let value: PolyglotValue = MockValue::string("kept").receive();
+        let raw = Handle::new(&value).into_raw();
+        let handle = unsafe { Handle::<PolyglotValue>::from_raw(raw) }.unwrap();
+        assert_eq!(handle.get().to_rust_string().unwrap(), "kept");
+        assert!(unsafe { Handle::<PolyglotValue>::from_raw(value.pass()) }.is_none());
+    }
+}
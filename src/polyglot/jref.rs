@@ -0,0 +1,264 @@
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ops::Deref;
+
+use super::{ConversionError, Pass, Receive, TryReceive, Value};
+
+/// A borrowed reference to a Java object, which can't outlive the wrapper it was borrowed from.
+///
+/// Wrappers like the structs generated by `class!` each own a reference to a Java object, and
+/// aren't `Copy`, so a reference can't be shared by accident.  A `JRef` lends out the object
+/// without giving up that ownership:  it dereferences to `&T`, so only methods taking `&self` can
+/// be called through it, and it can be passed to polyglot like the wrapper itself.
+/// ```rust
+/// fn print_size(list: JRef<'_, ArrayList<i32, i32>>) {
+///     println!("{}", list.size());
+/// }
+///
+/// print_size(JRef::new(&list));
+/// ```
+pub struct JRef<'a, T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    value: ManuallyDrop<T>,
+    phantom: PhantomData<&'a T>,
+}
+
+impl<'a, T> JRef<'a, T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    pub fn new(value: &'a T) -> Self {
+        Self {
+            value: ManuallyDrop::new(unsafe { T::from_polyglot_value(value.pass()) }),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates an owned read-only reference to the object, which may outlive this `JRef`.
+    pub fn clone_ref(&self) -> ReadOnly<T> {
+        unsafe { ReadOnly::from_polyglot_value(self.value.pass()) }
+    }
+}
+
+impl<'a, T> From<&'a T> for JRef<'a, T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    fn from(value: &'a T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<'a, T> Clone for JRef<'a, T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            value: ManuallyDrop::new(unsafe { T::from_polyglot_value(self.value.pass()) }),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Deref for JRef<'a, T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+unsafe impl<'a, T> Pass<*mut Value> for JRef<'a, T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    fn pass(&self) -> *mut Value {
+        self.value.pass()
+    }
+}
+
+/// An owned reference to a Java object that only lends it out as `&T`, so only the methods taking
+/// `&self` can be called through it.  Stubs marked `mut` take `&mut self`, so a `ReadOnly` view can
+/// be handed out without letting the object be modified, like an unmodifiable collection.
+///
+/// Unlike a `JRef`, it isn't tied to the wrapper it came from.  A wrapper can be turned into a view
+/// with `ReadOnly::from`, but there's no way back to a wrapper, and cloning a view gives another
+/// view.  Views can be passed to polyglot and received like wrappers, so `ReadOnly<T>` can be used
+/// as a type argument:
+/// ```rust
+/// let mut lists: ArrayList<ReadOnly<ArrayList<i32, i32>>, _> = ArrayList::new();
+/// lists.add(ReadOnly::from(ArrayList::new()));
+/// println!("{}", lists.get(0).size());
+/// ```
+pub struct ReadOnly<T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    value: T,
+}
+
+impl<T> ReadOnly<T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    /// Creates another read-only reference to the object.
+    pub fn clone_ref(&self) -> Self {
+        self.clone()
+    }
+}
+
+impl<T> From<T> for ReadOnly<T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    fn from(value: T) -> Self {
+        Self { value }
+    }
+}
+
+impl<T> Clone for ReadOnly<T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    fn clone(&self) -> Self {
+        Self::from(unsafe { T::from_polyglot_value(self.value.pass()) })
+    }
+}
+
+impl<T> Deref for ReadOnly<T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+unsafe impl<T> Pass<*mut Value> for ReadOnly<T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    fn pass(&self) -> *mut Value {
+        self.value.pass()
+    }
+}
+
+unsafe impl<T> Receive for ReadOnly<T>
+    where
+        T: Receive + Pass<*mut Value>,
+{
+    unsafe fn from_polyglot_value(value: *mut Value) -> Self {
+        Self::from(T::from_polyglot_value(value))
+    }
+}
+
+unsafe impl<T> TryReceive for ReadOnly<T>
+    where
+        T: TryReceive + Pass<*mut Value>,
+{
+    unsafe fn try_from_polyglot_value(value: *mut Value) -> Result<Self, ConversionError> {
+        T::try_from_polyglot_value(value).map(Self::from)
+    }
+}
+
+impl<T> fmt::Display for ReadOnly<T>
+    where
+        T: Receive + Pass<*mut Value> + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T> fmt::Debug for ReadOnly<T>
+    where
+        T: Receive + Pass<*mut Value> + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ReadOnly").field(&self.value).finish()
+    }
+}
+
+impl<T> PartialEq for ReadOnly<T>
+    where
+        T: Receive + Pass<*mut Value> + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T> Eq for ReadOnly<T> where T: Receive + Pass<*mut Value> + Eq {}
+
+impl<T> Hash for ReadOnly<T>
+    where
+        T: Receive + Pass<*mut Value> + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state)
+    }
+}
+
+impl<T> PartialOrd for ReadOnly<T>
+    where
+        T: Receive + Pass<*mut Value> + PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T> Ord for ReadOnly<T>
+    where
+        T: Receive + Pass<*mut Value> + Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builtins::ArrayList;
+
+    #[test]
+    fn views_share_the_object() {
+        let mut list = ArrayList::<i32, i32>::new();
+        let view = list.clone_ref();
+        list.add(1);
+        assert_eq!(view.size(), 1);
+        assert_eq!(view.clone().get(0), 1);
+        assert_eq!(view, view.clone_ref());
+        assert_eq!(format!("{:?}", view), "ReadOnly(java.util.ArrayList(\"[1]\"))");
+    }
+
+    #[test]
+    fn views_can_be_type_arguments() {
+        let mut lists = ArrayList::<ReadOnly<ArrayList<i32, i32>>, _>::new();
+        lists.add(ReadOnly::from(ArrayList::new()));
+        assert!(lists.get(0).is_empty());
+    }
+
+    #[test]
+    fn clone_ref_is_read_only() {
+        let mut lists = ArrayList::<ReadOnly<ArrayList<i32, i32>>, _>::new();
+        let mut list = ArrayList::new();
+        lists.add(list.clone_ref());
+        list.add(1);
+        assert_eq!(lists.get(0).get(0), 1);
+        let borrowed = JRef::new(&list);
+        assert_eq!(borrowed.clone_ref().size(), 1);
+    }
+}
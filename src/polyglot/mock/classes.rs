@@ -0,0 +1,554 @@
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+use super::{throw, Constructor, Method, MockObject, MockValue};
+
+/// A fake Java class.
+pub struct MockClass {
+    pub name: String,
+    /// Creates instances.  If it returns an object without a class, the object becomes an instance
+    /// of this class.
+    pub constructor: Option<Constructor>,
+    pub methods: HashMap<String, Method>,
+    pub static_methods: HashMap<String, Method>,
+    pub statics: BTreeMap<String, MockValue>,
+}
+
+impl MockClass {
+    /// A class with no constructor and no methods.  Every object also responds to `toString`,
+    /// `equals` and `hashCode`.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            constructor: None,
+            methods: HashMap::new(),
+            static_methods: HashMap::new(),
+            statics: BTreeMap::new(),
+        }
+    }
+
+    pub fn constructor(
+        mut self,
+        constructor: impl Fn(&[MockValue]) -> MockValue + 'static,
+    ) -> Self {
+        self.constructor = Some(Rc::new(constructor));
+        self
+    }
+
+    pub fn method(
+        mut self,
+        name: &str,
+        method: impl Fn(&MockValue, &[MockValue]) -> MockValue + 'static,
+    ) -> Self {
+        self.methods.insert(name.to_owned(), Rc::new(method));
+        self
+    }
+
+    /// Adds a static method.  It receives the class as `this`.
+    pub fn static_method(
+        mut self,
+        name: &str,
+        method: impl Fn(&MockValue, &[MockValue]) -> MockValue + 'static,
+    ) -> Self {
+        self.static_methods.insert(name.to_owned(), Rc::new(method));
+        self
+    }
+
+    pub fn static_member(mut self, name: &str, value: impl Into<MockValue>) -> Self {
+        self.statics.insert(name.to_owned(), value.into());
+        self
+    }
+
+    pub fn has_method(&self, name: &str) -> bool {
+        self.methods.contains_key(name) || OBJECT_METHODS.contains(&name)
+    }
+}
+
+const OBJECT_METHODS: [&str; 3] = ["toString", "equals", "hashCode"];
+
+pub(super) fn invoke(this: &MockValue, name: &str, args: &[MockValue]) -> MockValue {
+    if let MockValue::Class(class) = this {
+        return match class.static_methods.get(name) {
+            Some(method) => method(this, args),
+            None if name == "isInstance" => {
+                arity(name, args, 1);
+                MockValue::Boolean(is_instance(class, &args[0]))
+            }
+            None => unknown_identifier(&class.name, name),
+        };
+    }
+    if let Some(method) = this
+        .class()
+        .and_then(|class| class.methods.get(name).cloned())
+    {
+        return method(this, args);
+    }
+    match name {
+        "toString" => {
+            arity(name, args, 0);
+            MockValue::string(&this.to_string())
+        }
+        "equals" => {
+            arity(name, args, 1);
+            MockValue::Boolean(this.java_equals(&args[0]))
+        }
+        "hashCode" => {
+            arity(name, args, 0);
+            MockValue::from(this.java_hash_code())
+        }
+        _ => unknown_identifier(&format!("{:?}", this), name),
+    }
+}
+
+/// `isInstance`, for the class's `class` member.  Mock classes don't have superclasses, so every
+/// value but null is an `Object`, and other values are only instances of their own class.
+fn is_instance(class: &MockClass, value: &MockValue) -> bool {
+    match value {
+        MockValue::Null => false,
+        _ if class.name == "java.lang.Object" => true,
+        _ => value.class().is_some_and(|own| own.name == class.name),
+    }
+}
+
+pub(super) fn builtin() -> Vec<MockClass> {
+    vec![object(), string(), boolean(), character(), array_list(), hash_map()]
+}
+
+fn unknown_identifier(receiver: &str, name: &str) -> ! {
+    panic!(
+        "UnknownIdentifierException: {} has no method {}",
+        receiver, name
+    )
+}
+
+fn arity(name: &str, args: &[MockValue], expected: usize) {
+    if args.len() != expected {
+        panic!(
+            "ArityException: {}() takes {} arguments, but {} were passed",
+            name,
+            expected,
+            args.len()
+        );
+    }
+}
+
+fn int_arg(args: &[MockValue], index: usize) -> i64 {
+    args[index]
+        .as_i64()
+        .unwrap_or_else(|| panic!("expected an int argument, found {:?}", args[index]))
+}
+
+fn string_arg(args: &[MockValue], index: usize) -> Rc<Vec<u16>> {
+    match &args[index] {
+        MockValue::String(units) => units.clone(),
+        value => panic!("expected a String argument, found {:?}", value),
+    }
+}
+
+fn string_of(this: &MockValue) -> Rc<Vec<u16>> {
+    string_arg(core::slice::from_ref(this), 0)
+}
+
+fn check_index(index: i64, len: usize) -> usize {
+    if index < 0 || index as usize >= len {
+        throw(
+            "java.lang.IndexOutOfBoundsException",
+            format_args!("Index {} out of bounds for length {}", index, len),
+        );
+    }
+    index as usize
+}
+
+fn position(elements: &[MockValue], item: &MockValue) -> Option<usize> {
+    elements
+        .iter()
+        .position(|element| element.java_equals(item))
+}
+
+fn object() -> MockClass {
+    MockClass::new("java.lang.Object").constructor(|_| MockValue::object())
+}
+
+fn string() -> MockClass {
+    let units = |units: &[u16]| MockValue::String(Rc::new(units.to_vec()));
+    MockClass::new("java.lang.String")
+        .constructor(|args| match args {
+            [] => MockValue::string(""),
+            [value @ MockValue::String(_)] => value.clone(),
+            _ => panic!("no String constructor takes {:?}", args),
+        })
+        .method("length", |this, _| {
+            MockValue::from(string_of(this).len() as i32)
+        })
+        .method("isEmpty", |this, _| {
+            MockValue::Boolean(string_of(this).is_empty())
+        })
+        .method("charAt", move |this, args| {
+            let string = string_of(this);
+            let index = check_index(int_arg(args, 0), string.len());
+            units(&string[index..=index])
+        })
+        .method("substring", move |this, args| {
+            let string = string_of(this);
+            let end = if args.len() > 1 {
+                int_arg(args, 1)
+            } else {
+                string.len() as i64
+            };
+            let begin = int_arg(args, 0);
+            if begin < 0 || end < begin || end as usize > string.len() {
+                throw(
+                    "java.lang.StringIndexOutOfBoundsException",
+                    format_args!("begin {}, end {}, length {}", begin, end, string.len()),
+                );
+            }
+            units(&string[begin as usize..end as usize])
+        })
+        .method("concat", move |this, args| {
+            units(&[&string_of(this)[..], &string_arg(args, 0)[..]].concat())
+        })
+        .method("indexOf", |this, args| {
+            let (string, needle) = (string_of(this), string_arg(args, 0));
+            let index = (0..=string.len().saturating_sub(needle.len()))
+                .find(|&i| string[i..].starts_with(&needle))
+                .filter(|_| needle.len() <= string.len());
+            MockValue::from(index.map_or(-1, |i| i as i32))
+        })
+        .method("contains", |this, args| {
+            let (string, needle) = (string_of(this), string_arg(args, 0));
+            let found = needle.is_empty() || string.windows(needle.len()).any(|w| w == &needle[..]);
+            MockValue::Boolean(found)
+        })
+        .method("startsWith", |this, args| {
+            MockValue::Boolean(string_of(this).starts_with(&string_arg(args, 0)))
+        })
+        .method("endsWith", |this, args| {
+            MockValue::Boolean(string_of(this).ends_with(&string_arg(args, 0)))
+        })
+        .method("toUpperCase", |this, _| {
+            MockValue::string(&this.to_string().to_uppercase())
+        })
+        .method("toLowerCase", |this, _| {
+            MockValue::string(&this.to_string().to_lowercase())
+        })
+        .method("trim", move |this, _| {
+            let string = string_of(this);
+            let begin = string
+                .iter()
+                .position(|&u| u > 0x20)
+                .unwrap_or(string.len());
+            let end = string
+                .iter()
+                .rposition(|&u| u > 0x20)
+                .map_or(begin, |i| i + 1);
+            units(&string[begin..end])
+        })
+        .method("compareTo", |this, args| {
+            let (a, b) = (string_of(this), string_arg(args, 0));
+            let difference = a
+                .iter()
+                .zip(b.iter())
+                .find(|(x, y)| x != y)
+                .map_or(a.len() as i32 - b.len() as i32, |(&x, &y)| {
+                    x as i32 - y as i32
+                });
+            MockValue::from(difference)
+        })
+}
+
+fn boolean() -> MockClass {
+    MockClass::new("java.lang.Boolean")
+        .constructor(|args| match args {
+            [value @ MockValue::Boolean(_)] => value.clone(),
+            _ => panic!("no Boolean constructor takes {:?}", args),
+        })
+        .static_member("TRUE", true)
+        .static_member("FALSE", false)
+        .static_method("valueOf", |_, args| match args {
+            [value @ MockValue::Boolean(_)] => value.clone(),
+            [MockValue::String(string)] => {
+                MockValue::Boolean(String::from_utf16_lossy(string).eq_ignore_ascii_case("true"))
+            }
+            _ => panic!("no Boolean.valueOf takes {:?}", args),
+        })
+}
+
+/// Characters are single-character strings, like Java `char`s are to other languages.
+fn character() -> MockClass {
+    MockClass::new("java.lang.Character")
+        .static_method("valueOf", |_, args| match args {
+            [MockValue::Int(unit)] if (0..=0xffff).contains(unit) => {
+                MockValue::String(Rc::new(vec![*unit as u16]))
+            }
+            [value @ MockValue::String(string)] if string.len() == 1 => value.clone(),
+            _ => panic!("no Character.valueOf takes {:?}", args),
+        })
+        .static_method("hashCode", |_, args| match args {
+            [MockValue::String(string)] if string.len() == 1 => MockValue::from(string[0] as i32),
+            _ => panic!("no Character.hashCode takes {:?}", args),
+        })
+}
+
+/// Runs `f` on the elements of a list.
+fn with_list<R>(this: &MockValue, f: impl FnOnce(&mut Vec<MockValue>) -> R) -> R {
+    let object = this.as_object().expect("not a list");
+    let mut object = object.borrow_mut();
+    f(object.elements.as_mut().expect("not a list"))
+}
+
+fn list(elements: Vec<MockValue>) -> MockValue {
+    MockValue::from(MockObject {
+        elements: Some(elements),
+        ..MockObject::default()
+    })
+}
+
+fn array_list() -> MockClass {
+    MockClass::new("java.util.ArrayList")
+        .constructor(|args| match args {
+            [] | [MockValue::Int(_)] => list(Vec::new()),
+            [other] => match other.as_object().and_then(|o| o.borrow().elements.clone()) {
+                Some(elements) => list(elements),
+                None => panic!("no ArrayList constructor takes {:?}", args),
+            },
+            _ => panic!("no ArrayList constructor takes {:?}", args),
+        })
+        .method("size", |this, _| {
+            MockValue::from(with_list(this, |l| l.len() as i32))
+        })
+        .method("isEmpty", |this, _| {
+            MockValue::Boolean(with_list(this, |l| l.is_empty()))
+        })
+        .method("get", |this, args| {
+            with_list(this, |l| l[check_index(int_arg(args, 0), l.len())].clone())
+        })
+        .method("set", |this, args| {
+            with_list(this, |l| {
+                let index = check_index(int_arg(args, 0), l.len());
+                core::mem::replace(&mut l[index], args[1].clone())
+            })
+        })
+        .method("add", |this, args| {
+            with_list(this, |l| match args {
+                [element] => {
+                    l.push(element.clone());
+                    MockValue::Boolean(true)
+                }
+                [index, element] => {
+                    let index = index.as_i64().unwrap_or(-1);
+                    if index != l.len() as i64 {
+                        check_index(index, l.len());
+                    }
+                    l.insert(index as usize, element.clone());
+                    MockValue::Null
+                }
+                _ => panic!("no ArrayList.add takes {:?}", args),
+            })
+        })
+        .method("remove", |this, args| {
+            with_list(this, |l| match &args[0] {
+                MockValue::Int(index) => l.remove(check_index(*index, l.len())),
+                item => match position(l, item) {
+                    Some(index) => {
+                        l.remove(index);
+                        MockValue::Boolean(true)
+                    }
+                    None => MockValue::Boolean(false),
+                },
+            })
+        })
+        .method("removeRange", |this, args| {
+            with_list(this, |l| {
+                let (from, to) = (int_arg(args, 0), int_arg(args, 1));
+                if from < 0 || to < from || to as usize > l.len() {
+                    throw(
+                        "java.lang.IndexOutOfBoundsException",
+                        format_args!("{}..{}", from, to),
+                    );
+                }
+                l.drain(from as usize..to as usize);
+                MockValue::Null
+            })
+        })
+        .method("clear", |this, _| {
+            with_list(this, |l| l.clear());
+            MockValue::Null
+        })
+        .method("contains", |this, args| {
+            MockValue::Boolean(with_list(this, |l| position(l, &args[0]).is_some()))
+        })
+        .method("indexOf", |this, args| {
+            MockValue::from(with_list(this, |l| {
+                position(l, &args[0]).map_or(-1, |i| i as i32)
+            }))
+        })
+        .method("lastIndexOf", |this, args| {
+            let index = with_list(this, |l| l.iter().rposition(|e| e.java_equals(&args[0])));
+            MockValue::from(index.map_or(-1, |i| i as i32))
+        })
+        .method("toArray", |this, _| {
+            MockValue::array(with_list(this, |l| l.clone()))
+        })
+        .method("trimToSize", |_, _| MockValue::Null)
+        .method("ensureCapacity", |_, _| MockValue::Null)
+}
+
+fn with_map<R>(this: &MockValue, f: impl FnOnce(&mut Vec<(MockValue, MockValue)>) -> R) -> R {
+    let object = this.as_object().expect("not a map");
+    let mut object = object.borrow_mut();
+    f(&mut object.entries)
+}
+
+fn get_entry(entries: &[(MockValue, MockValue)], key: &MockValue) -> Option<usize> {
+    entries.iter().position(|(k, _)| k.java_equals(key))
+}
+
+fn hash_map() -> MockClass {
+    MockClass::new("java.util.HashMap")
+        .constructor(|args| match args {
+            [] | [MockValue::Int(_)] => MockValue::object(),
+            _ => panic!("no HashMap constructor takes {:?}", args),
+        })
+        .method("size", |this, _| {
+            MockValue::from(with_map(this, |m| m.len() as i32))
+        })
+        .method("isEmpty", |this, _| {
+            MockValue::Boolean(with_map(this, |m| m.is_empty()))
+        })
+        .method("put", |this, args| {
+            with_map(this, |m| match get_entry(m, &args[0]) {
+                Some(index) => core::mem::replace(&mut m[index].1, args[1].clone()),
+                None => {
+                    m.push((args[0].clone(), args[1].clone()));
+                    MockValue::Null
+                }
+            })
+        })
+        .method("get", |this, args| {
+            with_map(this, |m| {
+                get_entry(m, &args[0]).map_or(MockValue::Null, |i| m[i].1.clone())
+            })
+        })
+        .method("getOrDefault", |this, args| {
+            with_map(this, |m| {
+                get_entry(m, &args[0]).map_or(args[1].clone(), |i| m[i].1.clone())
+            })
+        })
+        .method("containsKey", |this, args| {
+            MockValue::Boolean(with_map(this, |m| get_entry(m, &args[0]).is_some()))
+        })
+        .method("containsValue", |this, args| {
+            MockValue::Boolean(with_map(this, |m| {
+                m.iter().any(|(_, v)| v.java_equals(&args[0]))
+            }))
+        })
+        .method("remove", |this, args| {
+            with_map(this, |m| match get_entry(m, &args[0]) {
+                Some(index) => m.remove(index).1,
+                None => MockValue::Null,
+            })
+        })
+        .method("clear", |this, _| {
+            with_map(this, |m| m.clear());
+            MockValue::Null
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::find_class;
+    use super::*;
+    use crate::polyglot::{Pass, PolyglotValue};
+
+    fn new(class: &str) -> PolyglotValue {
+        let class: PolyglotValue = MockValue::Class(find_class(class).unwrap()).receive();
+        class.new_instance(())
+    }
+
+    fn string(s: &str) -> PolyglotValue {
+        MockValue::string(s).receive()
+    }
+
+    #[test]
+    fn array_list_behaves_like_java() {
+        let list = new("java.util.ArrayList");
+        list.invoke("add", (string("a").pass(),));
+        list.invoke("add", (string("c").pass(),));
+        list.invoke("add", (1, string("b").pass()));
+        assert_eq!(list.invoke("size", ()).receive::<i32>(), Ok(3));
+        assert_eq!(list.invoke("indexOf", (string("c").pass(),)).receive::<i32>(), Ok(2));
+        assert_eq!(list.invoke("remove", (string("a").pass(),)).receive::<bool>(), Ok(true));
+        assert_eq!(list.invoke("toString", ()).to_rust_string().unwrap(), "[b, c]");
+        let array = list.invoke("toArray", ());
+        assert_eq!(array.array_len(), 2);
+        list.invoke("clear", ());
+        assert_eq!(list.invoke("isEmpty", ()).receive::<bool>(), Ok(true));
+    }
+
+    #[test]
+    #[should_panic(expected = "java.lang.IndexOutOfBoundsException: Index 2 out of bounds for length 0")]
+    fn array_list_checks_indices() {
+        new("java.util.ArrayList").invoke("get", (2,));
+    }
+
+    #[test]
+    fn hash_map_behaves_like_java() {
+        let map = new("java.util.HashMap");
+        assert!(map.invoke("put", (string("k").pass(), 1)).is_null());
+        assert_eq!(map.invoke("put", (string("k").pass(), 2)).receive::<i32>(), Ok(1));
+        assert_eq!(map.invoke("get", (string("k").pass(),)).receive::<i32>(), Ok(2));
+        assert!(map.invoke("get", (string("x").pass(),)).is_null());
+        let default = map.invoke("getOrDefault", (string("x").pass(), 7));
+        assert_eq!(default.receive::<i32>(), Ok(7));
+        assert_eq!(map.invoke("toString", ()).to_rust_string().unwrap(), "{k=2}");
+        assert_eq!(map.invoke("remove", (string("k").pass(),)).receive::<i32>(), Ok(2));
+        assert_eq!(map.invoke("size", ()).receive::<i32>(), Ok(0));
+    }
+
+    #[test]
+    fn string_behaves_like_java() {
+        let s = string("  Hello ");
+        let trimmed = s.invoke("trim", ());
+        assert_eq!(trimmed.to_rust_string().unwrap(), "Hello");
+        assert_eq!(trimmed.invoke("length", ()).receive::<i32>(), Ok(5));
+        let upper = trimmed.invoke("toUpperCase", ());
+        assert_eq!(upper.to_rust_string().unwrap(), "HELLO");
+        let compared = string("a").invoke("compareTo", (string("b").pass(),));
+        assert_eq!(compared.receive::<i32>(), Ok(-1));
+        let found = trimmed.invoke("indexOf", (string("lo").pass(),));
+        assert_eq!(found.receive::<i32>(), Ok(3));
+    }
+
+    #[test]
+    fn every_object_has_the_object_methods() {
+        let object = new("java.lang.Object");
+        assert_eq!(object.invoke("equals", (object.pass(),)).receive::<bool>(), Ok(true));
+        let hash = object.invoke("hashCode", ()).receive::<i32>().unwrap();
+        let text = object.invoke("toString", ()).to_rust_string().unwrap();
+        assert_eq!(text, format!("java.lang.Object@{:x}", hash));
+    }
+
+    #[test]
+    fn classes_answer_is_instance() {
+        let class = |name| {
+            let class: PolyglotValue = MockValue::Class(find_class(name).unwrap()).receive();
+            class.get_member("class").unwrap()
+        };
+        let list = new("java.util.ArrayList");
+        let is_instance = |class: &PolyglotValue, value: &PolyglotValue| {
+            class.invoke("isInstance", (value.pass(),)).receive::<bool>().unwrap()
+        };
+        assert!(is_instance(&class("java.util.ArrayList"), &list));
+        assert!(is_instance(&class("java.lang.Object"), &list));
+        assert!(!is_instance(&class("java.util.HashMap"), &list));
+        assert!(is_instance(&class("java.lang.String"), &string("s")));
+        let null: PolyglotValue = MockValue::Null.receive();
+        assert!(!is_instance(&class("java.lang.Object"), &null));
+    }
+
+    #[test]
+    #[should_panic(expected = "UnknownIdentifierException: [] has no method frobnicate")]
+    fn unknown_methods_throw() {
+        new("java.util.ArrayList").invoke("frobnicate", ());
+    }
+}
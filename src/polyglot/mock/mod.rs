@@ -0,0 +1,1090 @@
+//! An in-process stand-in for the GraalVM polyglot runtime, used by unit tests and enabled outside
+//! of them by the `mock-runtime` feature.
+//!
+//! The mock defines the `polyglot_*` symbols that GraalVM's LLVM runtime normally provides, so
+//! bindings and generated code can be run natively with `cargo test`.  Values are modelled by
+//! `MockValue`:  numbers, booleans, UTF-16 strings, objects with members and array elements, and
+//! classes.  A few Java classes are registered by default (`java.lang.Object`, `java.lang.String`,
+//! `java.lang.Boolean`, `java.lang.Character`, `java.util.ArrayList` and `java.util.HashMap`), and
+//! tests can register their own with `register_class`.
+//!
+//! Every pointer the mock hands out is a leaked `MockValue`, so values are never freed.  The state
+//! of the runtime (classes, polyglot bindings, program arguments and handles) is per-thread, which
+//! keeps tests that run in parallel independent of each other.
+
+mod classes;
+
+use core::cell::RefCell;
+use core::fmt;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::CStr;
+use std::rc::Rc;
+
+use super::{PolyglotValue, Receive, Value};
+
+pub use classes::MockClass;
+
+/// A method of a mock class.  It receives the object it was invoked on and the arguments.
+pub type Method = Rc<dyn Fn(&MockValue, &[MockValue]) -> MockValue>;
+
+/// A constructor of a mock class.
+pub type Constructor = Rc<dyn Fn(&[MockValue]) -> MockValue>;
+
+/// An evaluator for `polyglot_eval` and `polyglot_eval_file`.  It receives the language id and the
+/// source code or file name.
+pub type Evaluator = Rc<dyn Fn(&str, &str) -> MockValue>;
+
+/// A value in the mock runtime.
+#[derive(Clone)]
+pub enum MockValue {
+    Null,
+    Boolean(bool),
+    Int(i64),
+    Double(f64),
+    /// A string, stored as UTF-16 like a Java string.
+    String(Rc<Vec<u16>>),
+    Object(Rc<RefCell<MockObject>>),
+    Class(Rc<MockClass>),
+}
+
+/// An object in the mock runtime.  Objects without a class behave like JavaScript objects:  they
+/// only have members.
+#[derive(Default)]
+pub struct MockObject {
+    pub class: Option<Rc<MockClass>>,
+    pub members: BTreeMap<String, MockValue>,
+    /// The elements of an array or list, or `None` if the object has no array elements.
+    pub elements: Option<Vec<MockValue>>,
+    /// Arrays can't grow or shrink;  lists can.
+    pub fixed_size: bool,
+    /// The entries of a map, in insertion order.
+    pub entries: Vec<(MockValue, MockValue)>,
+}
+
+/// A variadic argument, as passed to `polyglot_invoke` and friends.
+#[derive(Clone, Copy, Debug)]
+pub enum Arg {
+    Int(i64),
+    Double(f64),
+    Value(*mut Value),
+}
+
+impl From<i32> for Arg {
+    fn from(value: i32) -> Self {
+        Arg::Int(value as i64)
+    }
+}
+
+impl From<i64> for Arg {
+    fn from(value: i64) -> Self {
+        Arg::Int(value)
+    }
+}
+
+impl From<f64> for Arg {
+    fn from(value: f64) -> Self {
+        Arg::Double(value)
+    }
+}
+
+impl From<*mut Value> for Arg {
+    fn from(value: *mut Value) -> Self {
+        Arg::Value(value)
+    }
+}
+
+impl From<*const Value> for Arg {
+    fn from(value: *const Value) -> Self {
+        Arg::Value(value as *mut Value)
+    }
+}
+
+impl Arg {
+    fn into_mock(self) -> MockValue {
+        match self {
+            Arg::Int(i) => MockValue::Int(i),
+            Arg::Double(d) => MockValue::Double(d),
+            Arg::Value(ptr) => unsafe { MockValue::from_ptr(ptr) },
+        }
+    }
+}
+
+impl MockValue {
+    pub fn string(string: &str) -> Self {
+        MockValue::String(Rc::new(string.encode_utf16().collect()))
+    }
+
+    /// A new object with no class and no members.
+    pub fn object() -> Self {
+        MockValue::from(MockObject::default())
+    }
+
+    /// A new fixed-size array holding `elements`.
+    pub fn array(elements: Vec<MockValue>) -> Self {
+        MockValue::from(MockObject {
+            elements: Some(elements),
+            fixed_size: true,
+            ..MockObject::default()
+        })
+    }
+
+    /// A new instance of `class` with the given state.
+    pub fn instance(class: &Rc<MockClass>, mut object: MockObject) -> Self {
+        object.class = Some(class.clone());
+        MockValue::from(object)
+    }
+
+    /// Reads the value behind a polyglot pointer.  A null pointer reads as `MockValue::Null`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or have been returned by the mock runtime.
+    pub unsafe fn from_ptr(ptr: *const Value) -> Self {
+        if ptr.is_null() {
+            MockValue::Null
+        } else {
+            (*(ptr as *const MockValue)).clone()
+        }
+    }
+
+    /// Leaks the value and returns a polyglot pointer to it.
+    pub fn into_ptr(self) -> *mut Value {
+        Box::into_raw(Box::new(self)) as *mut Value
+    }
+
+    /// Converts the value to a Rust type, as if it were returned by the polyglot runtime.
+    pub fn receive<T: Receive>(self) -> T {
+        // The pointer is a leaked `MockValue`, which is what the mock's polyglot values are.
+        unsafe { T::from_polyglot_value(self.into_ptr()) }
+    }
+
+    /// The value as a Rust string, if it is a string.
+    pub fn as_str(&self) -> Option<String> {
+        match self {
+            MockValue::String(units) => Some(String::from_utf16_lossy(units)),
+            _ => None,
+        }
+    }
+
+    /// The value as an `i64`, if it is an integer, or a double with no fractional part.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            MockValue::Int(i) => Some(i),
+            MockValue::Double(d) if d.fract() == 0.0 && d.abs() < 9.223_372_036_854_776e18 => {
+                Some(d as i64)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Rc<RefCell<MockObject>>> {
+        match self {
+            MockValue::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    /// The class the value is an instance of, if it is a Java object.
+    pub fn class(&self) -> Option<Rc<MockClass>> {
+        match self {
+            MockValue::String(_) => find_class("java.lang.String"),
+            MockValue::Object(object) => object.borrow().class.clone(),
+            _ => None,
+        }
+    }
+
+    /// Compares two values like Java's `equals`:  strings and numbers by value, lists element by
+    /// element, and other objects by identity.
+    pub fn java_equals(&self, other: &MockValue) -> bool {
+        match (self, other) {
+            (MockValue::Null, MockValue::Null) => true,
+            (MockValue::Boolean(a), MockValue::Boolean(b)) => a == b,
+            (MockValue::Int(a), MockValue::Int(b)) => a == b,
+            (MockValue::Double(a), MockValue::Double(b)) => a.to_bits() == b.to_bits(),
+            (MockValue::String(a), MockValue::String(b)) => a == b,
+            (MockValue::Class(a), MockValue::Class(b)) => Rc::ptr_eq(a, b),
+            (MockValue::Object(a), MockValue::Object(b)) => {
+                if Rc::ptr_eq(a, b) {
+                    return true;
+                }
+                let (a, b) = (a.borrow(), b.borrow());
+                match (&a.elements, &b.elements) {
+                    (Some(x), Some(y)) if !a.fixed_size && !b.fixed_size => {
+                        x.len() == y.len() && x.iter().zip(y).all(|(x, y)| x.java_equals(y))
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// The result of Java's `hashCode`.
+    pub fn java_hash_code(&self) -> i32 {
+        match self {
+            MockValue::Null => 0,
+            MockValue::Boolean(b) => {
+                if *b {
+                    1231
+                } else {
+                    1237
+                }
+            }
+            MockValue::Int(i) => (*i ^ (*i >> 32)) as i32,
+            MockValue::Double(d) => {
+                let bits = d.to_bits();
+                (bits ^ (bits >> 32)) as i32
+            }
+            MockValue::String(units) => units.iter().fold(0i32, |hash, &unit| {
+                hash.wrapping_mul(31).wrapping_add(unit as i32)
+            }),
+            MockValue::Object(object) => {
+                match (&object.borrow().elements, object.borrow().fixed_size) {
+                    (Some(elements), false) => elements.iter().fold(1i32, |hash, element| {
+                        hash.wrapping_mul(31).wrapping_add(element.java_hash_code())
+                    }),
+                    _ => Rc::as_ptr(object) as usize as i32,
+                }
+            }
+            MockValue::Class(class) => Rc::as_ptr(class) as usize as i32,
+        }
+    }
+}
+
+impl From<MockObject> for MockValue {
+    fn from(object: MockObject) -> Self {
+        MockValue::Object(Rc::new(RefCell::new(object)))
+    }
+}
+
+impl From<bool> for MockValue {
+    fn from(value: bool) -> Self {
+        MockValue::Boolean(value)
+    }
+}
+
+impl From<i32> for MockValue {
+    fn from(value: i32) -> Self {
+        MockValue::Int(value as i64)
+    }
+}
+
+impl From<i64> for MockValue {
+    fn from(value: i64) -> Self {
+        MockValue::Int(value)
+    }
+}
+
+impl From<f64> for MockValue {
+    fn from(value: f64) -> Self {
+        MockValue::Double(value)
+    }
+}
+
+impl From<&str> for MockValue {
+    fn from(value: &str) -> Self {
+        MockValue::string(value)
+    }
+}
+
+impl From<PolyglotValue> for MockValue {
+    fn from(value: PolyglotValue) -> Self {
+        unsafe { MockValue::from_ptr(value.ptr) }
+    }
+}
+
+/// Formats the value like Java's `String.valueOf`.
+impl fmt::Display for MockValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MockValue::Null => f.write_str("null"),
+            MockValue::Boolean(b) => write!(f, "{}", b),
+            MockValue::Int(i) => write!(f, "{}", i),
+            MockValue::Double(d) => write!(f, "{:?}", d),
+            MockValue::String(units) => f.write_str(&String::from_utf16_lossy(units)),
+            MockValue::Class(class) => write!(f, "class {}", class.name),
+            MockValue::Object(object) => {
+                let object = object.borrow();
+                if let (Some(elements), false) = (&object.elements, object.fixed_size) {
+                    return write_joined(f, "[", elements.iter(), "]");
+                }
+                if object.class.as_ref().map(|class| class.name.as_str())
+                    == Some("java.util.HashMap")
+                {
+                    let entries = object.entries.iter().map(|(k, v)| format!("{}={}", k, v));
+                    return write_joined(f, "{", entries, "}");
+                }
+                let name = object
+                    .class
+                    .as_ref()
+                    .map_or("java.lang.Object", |c| &c.name);
+                write!(f, "{}@{:x}", name, self.java_hash_code())
+            }
+        }
+    }
+}
+
+impl fmt::Debug for MockValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MockValue::String(_) => write!(f, "{:?}", self.to_string()),
+            _ => write!(f, "{}", self),
+        }
+    }
+}
+
+fn write_joined<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    open: &str,
+    items: impl Iterator<Item = T>,
+    close: &str,
+) -> fmt::Result {
+    f.write_str(open)?;
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    f.write_str(close)
+}
+
+#[derive(Default)]
+struct Runtime {
+    classes: HashMap<String, Rc<MockClass>>,
+    bindings: HashMap<String, MockValue>,
+    args: Vec<MockValue>,
+    evaluator: Option<Evaluator>,
+    file_evaluator: Option<Evaluator>,
+    handles: HashMap<usize, MockValue>,
+    next_handle: usize,
+}
+
+impl Runtime {
+    fn new() -> Self {
+        let mut runtime = Runtime {
+            next_handle: 1,
+            ..Runtime::default()
+        };
+        for class in classes::builtin() {
+            runtime.classes.insert(class.name.clone(), Rc::new(class));
+        }
+        runtime
+    }
+}
+
+thread_local! {
+    static RUNTIME: RefCell<Runtime> = RefCell::new(Runtime::new());
+}
+
+fn with_runtime<R>(f: impl FnOnce(&mut Runtime) -> R) -> R {
+    RUNTIME.with(|runtime| f(&mut runtime.borrow_mut()))
+}
+
+/// Resets this thread's runtime:  classes registered by the test, polyglot bindings, arguments,
+/// evaluators and handles are all discarded.
+pub fn reset() {
+    with_runtime(|runtime| *runtime = Runtime::new());
+}
+
+/// Registers a class, replacing any class with the same name.
+pub fn register_class(class: MockClass) -> Rc<MockClass> {
+    let class = Rc::new(class);
+    with_runtime(|runtime| runtime.classes.insert(class.name.clone(), class.clone()));
+    class
+}
+
+/// Looks up a registered class by its fully qualified name.
+pub fn find_class(name: &str) -> Option<Rc<MockClass>> {
+    with_runtime(|runtime| runtime.classes.get(name).cloned())
+}
+
+/// Sets the arguments returned by `polyglot_get_arg`.
+pub fn set_args(args: Vec<MockValue>) {
+    with_runtime(|runtime| runtime.args = args);
+}
+
+/// Handles `polyglot_eval`.  Without an evaluator, evaluating code panics.
+pub fn on_eval(evaluator: impl Fn(&str, &str) -> MockValue + 'static) {
+    with_runtime(|runtime| runtime.evaluator = Some(Rc::new(evaluator)));
+}
+
+/// Handles `polyglot_eval_file`.  Without an evaluator, evaluating a file panics.
+pub fn on_eval_file(evaluator: impl Fn(&str, &str) -> MockValue + 'static) {
+    with_runtime(|runtime| runtime.file_evaluator = Some(Rc::new(evaluator)));
+}
+
+/// Reads a value exported with `polyglot_export`, or put in the bindings with `bind`.
+pub fn binding(name: &str) -> Option<MockValue> {
+    with_runtime(|runtime| runtime.bindings.get(name).cloned())
+}
+
+/// Puts a value in the polyglot bindings, for `polyglot_import` to find.
+pub fn bind(name: &str, value: impl Into<MockValue>) {
+    let value = value.into();
+    with_runtime(|runtime| runtime.bindings.insert(name.to_owned(), value));
+}
+
+/// Throws a Java exception, which the mock runtime models as a panic.
+pub fn throw(exception: &str, message: impl fmt::Display) -> ! {
+    panic!("{}: {}", exception, message)
+}
+
+unsafe fn read_str<'a>(ptr: *const i8) -> &'a str {
+    CStr::from_ptr(ptr)
+        .to_str()
+        .expect("polyglot names must be UTF-8")
+}
+
+/// Calls `method` on `object` with `args`.  This replaces the variadic `polyglot_invoke`, which
+/// stable Rust can't define.
+///
+/// # Safety
+///
+/// `object` must have been returned by the mock runtime and `name` must be a valid C string.
+pub unsafe fn invoke(object: *mut Value, name: *const i8, args: &[Arg]) -> *mut Value {
+    let name = read_str(name);
+    let this = MockValue::from_ptr(object);
+    let args: Vec<MockValue> = args.iter().map(|arg| arg.into_mock()).collect();
+    classes::invoke(&this, name, &args).into_ptr()
+}
+
+/// Instantiates `constructor` with `args`.  This replaces the variadic `polyglot_new_instance`.
+///
+/// # Safety
+///
+/// `constructor` must have been returned by the mock runtime.
+pub unsafe fn new_instance(constructor: *const Value, args: &[Arg]) -> *mut Value {
+    let args: Vec<MockValue> = args.iter().map(|arg| arg.into_mock()).collect();
+    match MockValue::from_ptr(constructor) {
+        MockValue::Class(class) => match &class.constructor {
+            Some(constructor) => {
+                let instance = constructor(&args);
+                if let MockValue::Object(object) = &instance {
+                    object
+                        .borrow_mut()
+                        .class
+                        .get_or_insert_with(|| class.clone());
+                }
+                instance.into_ptr()
+            }
+            None => throw("java.lang.InstantiationException", &class.name),
+        },
+        value => panic!("{:?} can't be instantiated", value),
+    }
+}
+
+/// Writes a member of `object`.  This replaces the variadic `polyglot_put_member`.
+///
+/// # Safety
+///
+/// `object` must have been returned by the mock runtime and `name` must be a valid C string.
+pub unsafe fn put_member(object: *mut Value, name: *const i8, value: Arg) {
+    let name = read_str(name);
+    match MockValue::from_ptr(object) {
+        MockValue::Object(object) => {
+            object
+                .borrow_mut()
+                .members
+                .insert(name.to_owned(), value.into_mock());
+        }
+        value => panic!("can't write member {} of {:?}", name, value),
+    }
+}
+
+/// Writes an element of `array`.  This replaces the variadic `polyglot_set_array_element`.
+///
+/// # Safety
+///
+/// `array` must have been returned by the mock runtime.
+pub unsafe fn set_array_element(array: *mut Value, idx: i32, value: Arg) {
+    let array = MockValue::from_ptr(array);
+    let object = array.as_object().expect("not an array");
+    let mut object = object.borrow_mut();
+    let elements = object.elements.as_mut().expect("not an array");
+    match elements.get_mut(idx as usize) {
+        Some(element) if idx >= 0 => *element = value.into_mock(),
+        _ => throw("java.lang.ArrayIndexOutOfBoundsException", idx),
+    }
+}
+
+/// The functions GraalVM's LLVM runtime exports, minus the variadic ones.  Their signatures match
+/// the declarations in `bindings.rs`, including the `C-unwind` ABI, so the panics that stand in for
+/// Java exceptions unwind to the caller instead of aborting the test binary.
+mod exports {
+    use super::*;
+    use crate::polyglot::strings::Charset;
+
+    unsafe fn get(value: *const Value) -> MockValue {
+        MockValue::from_ptr(value)
+    }
+
+    fn charset(name: *const i8) -> Charset {
+        let name = unsafe { read_str(name) };
+        match name.to_ascii_uppercase().as_str() {
+            "UTF-8" | "UTF8" => Charset::Utf8,
+            "UTF-16LE" => Charset::Utf16Le,
+            "UTF-16BE" | "UTF-16" => Charset::Utf16Be,
+            "ISO-8859-1" | "LATIN1" => Charset::Iso8859_1,
+            "US-ASCII" | "ASCII" => Charset::Ascii,
+            _ => throw("java.nio.charset.UnsupportedCharsetException", name),
+        }
+    }
+
+    /// Encodes like Java's `String.getBytes`, substituting `?` for unmappable characters.
+    fn encode(units: &[u16], charset: Charset) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match charset {
+            Charset::Utf16Le => units.iter().for_each(|u| bytes.extend(&u.to_le_bytes())),
+            Charset::Utf16Be => units.iter().for_each(|u| bytes.extend(&u.to_be_bytes())),
+            _ => {
+                let max = match charset {
+                    Charset::Iso8859_1 => 0xff,
+                    Charset::Ascii => 0x7f,
+                    _ => u32::MAX,
+                };
+                for c in core::char::decode_utf16(units.iter().cloned()) {
+                    match c {
+                        Ok(c) if c as u32 <= max && charset == Charset::Utf8 => {
+                            bytes.extend(c.encode_utf8(&mut [0; 4]).as_bytes())
+                        }
+                        Ok(c) if c as u32 <= max => bytes.push(c as u32 as u8),
+                        _ => bytes.push(b'?'),
+                    }
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Decodes like Java's `new String(bytes, charset)`, substituting U+FFFD for malformed input.
+    fn decode(bytes: &[u8], charset: Charset) -> Vec<u16> {
+        match charset {
+            Charset::Utf8 => String::from_utf8_lossy(bytes).encode_utf16().collect(),
+            Charset::Utf16Le | Charset::Utf16Be => {
+                let mut units: Vec<u16> = bytes
+                    .chunks_exact(2)
+                    .map(|unit| match charset {
+                        Charset::Utf16Le => u16::from_le_bytes([unit[0], unit[1]]),
+                        _ => u16::from_be_bytes([unit[0], unit[1]]),
+                    })
+                    .collect();
+                if bytes.len() % 2 == 1 {
+                    units.push(0xfffd);
+                }
+                units
+            }
+            Charset::Iso8859_1 => bytes.iter().map(|&b| b as u16).collect(),
+            Charset::Ascii => bytes
+                .iter()
+                .map(|&b| if b.is_ascii() { b as u16 } else { 0xfffd })
+                .collect(),
+        }
+    }
+
+    fn units(value: &MockValue) -> Rc<Vec<u16>> {
+        match value {
+            MockValue::String(units) => units.clone(),
+            value => panic!("{:?} is not a string", value),
+        }
+    }
+
+    fn number(value: &MockValue) -> f64 {
+        match *value {
+            MockValue::Int(i) => i as f64,
+            MockValue::Double(d) => d,
+            ref value => panic!("{:?} is not a number", value),
+        }
+    }
+
+    fn fits(value: &MockValue, min: i64, max: i64) -> bool {
+        match value.as_i64() {
+            Some(i) => min <= i && i <= max,
+            None => false,
+        }
+    }
+
+    fn integer(value: &MockValue) -> i64 {
+        match *value {
+            MockValue::Int(i) => i,
+            ref value => number(value) as i64,
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_import(name: *const i8) -> *mut Value {
+        binding(read_str(name))
+            .unwrap_or(MockValue::Null)
+            .into_ptr()
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_export(name: *const i8, value: *mut Value) {
+        bind(read_str(name), get(value));
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_eval(id: *const i8, code: *const i8) -> *mut Value {
+        let (id, code) = (read_str(id), read_str(code));
+        match with_runtime(|runtime| runtime.evaluator.clone()) {
+            Some(evaluator) => evaluator(id, code).into_ptr(),
+            None => panic!(
+                "the mock runtime can't evaluate {} code;  use mock::on_eval",
+                id
+            ),
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_eval_file(id: *const i8, filename: *const i8) -> *mut Value {
+        let (id, filename) = (read_str(id), read_str(filename));
+        match with_runtime(|runtime| runtime.file_evaluator.clone()) {
+            Some(evaluator) => evaluator(id, filename).into_ptr(),
+            None => panic!(
+                "the mock runtime can't evaluate {};  use mock::on_eval_file",
+                filename
+            ),
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_java_type(classname: *const i8) -> *mut Value {
+        match find_class(read_str(classname)) {
+            Some(class) => MockValue::Class(class).into_ptr(),
+            None => MockValue::Null.into_ptr(),
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_get_arg(i: i32) -> *mut Value {
+        with_runtime(|runtime| runtime.args.get(i as usize).cloned())
+            .unwrap_or(MockValue::Null)
+            .into_ptr()
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_get_arg_count() -> i32 {
+        with_runtime(|runtime| runtime.args.len() as i32)
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_is_value(value: *const Value) -> bool {
+        !value.is_null() && !_graalvm_llvm_is_handle(value as *mut Value)
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_is_null(value: *const Value) -> bool {
+        matches!(get(value), MockValue::Null)
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_is_number(value: *const Value) -> bool {
+        matches!(get(value), MockValue::Int(_) | MockValue::Double(_))
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_is_boolean(value: *const Value) -> bool {
+        matches!(get(value), MockValue::Boolean(_))
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_is_string(value: *const Value) -> bool {
+        matches!(get(value), MockValue::String(_))
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_fits_in_i8(value: *const Value) -> bool {
+        fits(&get(value), i8::MIN as i64, i8::MAX as i64)
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_fits_in_i16(value: *const Value) -> bool {
+        fits(&get(value), i16::MIN as i64, i16::MAX as i64)
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_fits_in_i32(value: *const Value) -> bool {
+        fits(&get(value), i32::MIN as i64, i32::MAX as i64)
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_fits_in_i64(value: *const Value) -> bool {
+        fits(&get(value), i64::MIN, i64::MAX)
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_fits_in_float(value: *const Value) -> bool {
+        match get(value) {
+            MockValue::Int(i) => (i as f32) as i64 == i && i.abs() <= 1 << 24,
+            MockValue::Double(d) => d.is_nan() || (d as f32) as f64 == d,
+            _ => false,
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_fits_in_double(value: *const Value) -> bool {
+        match get(value) {
+            MockValue::Int(i) => i.abs() <= 1 << 53,
+            MockValue::Double(_) => true,
+            _ => false,
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_as_i8(value: *const Value) -> i8 {
+        integer(&get(value)) as i8
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_as_i16(value: *const Value) -> i16 {
+        integer(&get(value)) as i16
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_as_i32(value: *const Value) -> i32 {
+        integer(&get(value)) as i32
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_as_i64(value: *const Value) -> i64 {
+        integer(&get(value))
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_as_float(value: *const Value) -> f32 {
+        number(&get(value)) as f32
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_as_double(value: *const Value) -> f64 {
+        number(&get(value))
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_as_boolean(value: *const Value) -> bool {
+        match get(value) {
+            MockValue::Boolean(b) => b,
+            value => panic!("{:?} is not a boolean", value),
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_can_execute(_value: *const Value) -> bool {
+        false
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_can_instantiate(object: *const Value) -> bool {
+        match get(object) {
+            MockValue::Class(class) => class.constructor.is_some(),
+            _ => false,
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_has_members(value: *const Value) -> bool {
+        matches!(get(value), MockValue::Object(_) | MockValue::Class(_))
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_has_member(value: *const Value, name: *const i8) -> bool {
+        let name = read_str(name);
+        match get(value) {
+            MockValue::Class(class) => name == "class" || class.statics.contains_key(name),
+            value => match value.as_object() {
+                Some(object) => {
+                    object.borrow().members.contains_key(name)
+                        || value
+                            .class()
+                            .map(|class| class.has_method(name))
+                            .unwrap_or(false)
+                }
+                None => false,
+            },
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_get_member(object: *const Value, name: *const i8) -> *mut Value {
+        let name = read_str(name);
+        let member = match get(object) {
+            // A class stands in for its own `java.lang.Class`, which only has `isInstance`.
+            MockValue::Class(class) if name == "class" => Some(MockValue::Class(class)),
+            MockValue::Class(class) => class.statics.get(name).cloned(),
+            MockValue::Object(object) => object.borrow().members.get(name).cloned(),
+            _ => None,
+        };
+        member.unwrap_or(MockValue::Null).into_ptr()
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_remove_member(object: *mut Value, name: *const i8) -> bool {
+        let name = read_str(name);
+        match get(object) {
+            MockValue::Object(object) => object.borrow_mut().members.remove(name).is_some(),
+            _ => false,
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_has_array_elements(value: *const Value) -> bool {
+        match get(value) {
+            MockValue::Object(object) => object.borrow().elements.is_some(),
+            _ => false,
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_get_array_size(array: *const Value) -> u64 {
+        match get(array) {
+            MockValue::Object(object) => {
+                object.borrow().elements.as_ref().map_or(0, Vec::len) as u64
+            }
+            _ => 0,
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_get_array_element(array: *const Value, idx: i32) -> *mut Value {
+        let array = get(array);
+        let element = array.as_object().and_then(|object| {
+            object
+                .borrow()
+                .elements
+                .as_ref()?
+                .get(idx as usize)
+                .cloned()
+        });
+        match element {
+            Some(element) if idx >= 0 => element.into_ptr(),
+            _ => throw("java.lang.ArrayIndexOutOfBoundsException", idx),
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_remove_array_element(array: *mut Value, idx: i32) -> bool {
+        let array = get(array);
+        let object = match array.as_object() {
+            Some(object) => object,
+            None => return false,
+        };
+        let mut object = object.borrow_mut();
+        if object.fixed_size {
+            return false;
+        }
+        match object.elements.as_mut() {
+            Some(elements) if idx >= 0 && (idx as usize) < elements.len() => {
+                elements.remove(idx as usize);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_get_string_size(value: *const Value) -> u64 {
+        units(&get(value)).len() as u64
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_as_string(
+        value: *const Value,
+        buffer: *mut i8,
+        bufsize: u64,
+        charset_name: *const i8,
+    ) -> u64 {
+        let charset = charset(charset_name);
+        let bytes = encode(&units(&get(value)), charset);
+        let written = bytes.len().min(bufsize as usize);
+        let buffer = core::slice::from_raw_parts_mut(buffer as *mut u8, bufsize as usize);
+        buffer[..written].copy_from_slice(&bytes[..written]);
+        let terminator = match charset {
+            Charset::Utf16Le | Charset::Utf16Be => 2,
+            _ => 1,
+        };
+        if written + terminator <= buffer.len() {
+            buffer[written..written + terminator]
+                .iter_mut()
+                .for_each(|b| *b = 0);
+        }
+        written as u64
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_from_string(
+        string: *const i8,
+        charset_name: *const i8,
+    ) -> *mut Value {
+        let charset = charset(charset_name);
+        let bytes = match charset {
+            Charset::Utf16Le | Charset::Utf16Be => {
+                let mut len = 0;
+                while *string.add(len) != 0 || *string.add(len + 1) != 0 {
+                    len += 2;
+                }
+                core::slice::from_raw_parts(string as *const u8, len)
+            }
+            _ => CStr::from_ptr(string).to_bytes(),
+        };
+        MockValue::String(Rc::new(decode(bytes, charset))).into_ptr()
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn polyglot_from_string_n(
+        string: *const i8,
+        size: u64,
+        charset_name: *const i8,
+    ) -> *mut Value {
+        let bytes = core::slice::from_raw_parts(string as *const u8, size as usize);
+        MockValue::String(Rc::new(decode(bytes, charset(charset_name)))).into_ptr()
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn _graalvm_llvm_create_handle(managed_object: *mut Value) -> *mut Value {
+        let value = get(managed_object);
+        with_runtime(|runtime| {
+            // Handles are odd, so they never alias a leaked `MockValue`.
+            let handle = runtime.next_handle * 2 + 1;
+            runtime.next_handle += 1;
+            runtime.handles.insert(handle, value);
+            handle as *mut Value
+        })
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn _graalvm_llvm_create_deref_handle(
+        managed_object: *mut Value,
+    ) -> *mut Value {
+        _graalvm_llvm_create_handle(managed_object)
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn _graalvm_llvm_resolve_handle(native_handle: *mut Value) -> *mut Value {
+        match with_runtime(|runtime| runtime.handles.get(&(native_handle as usize)).cloned()) {
+            Some(value) => value.into_ptr(),
+            None => panic!("{:p} is not a handle", native_handle),
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn _graalvm_llvm_release_handle(native_handle: *mut Value) {
+        with_runtime(|runtime| runtime.handles.remove(&(native_handle as usize)));
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn _graalvm_llvm_is_handle(native_handle: *mut Value) -> bool {
+        with_runtime(|runtime| runtime.handles.contains_key(&(native_handle as usize)))
+    }
+
+    #[no_mangle]
+    unsafe extern "C-unwind" fn _graalvm_llvm_points_to_handle_space(native_handle: *mut Value) -> bool {
+        native_handle as usize & 1 == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polyglot::strings::{self, Charset};
+    use crate::polyglot::{
+        _graalvm_llvm_create_handle, _graalvm_llvm_is_handle, _graalvm_llvm_release_handle,
+        _graalvm_llvm_resolve_handle, polyglot_as_string,
+    };
+
+    fn string_ptr(s: &str) -> *mut Value {
+        MockValue::string(s).into_ptr()
+    }
+
+    /// Calls `polyglot_as_string` and returns the number of bytes written.
+    fn as_string(value: &str, buffer: &mut [u8], charset: &[u8]) -> usize {
+        let charset = CStr::from_bytes_with_nul(charset).unwrap();
+        let written = unsafe {
+            polyglot_as_string(
+                string_ptr(value),
+                buffer.as_mut_ptr() as *mut i8,
+                buffer.len() as u64,
+                charset.as_ptr(),
+            )
+        };
+        written as usize
+    }
+
+    #[test]
+    fn registers_and_resets_classes() {
+        register_class(MockClass::new("com.example.Counter").constructor(|_| MockValue::object()));
+        assert!(find_class("com.example.Counter").unwrap().constructor.is_some());
+        reset();
+        assert!(find_class("com.example.Counter").is_none());
+        assert!(find_class("java.util.ArrayList").is_some());
+    }
+
+    #[test]
+    fn instances_get_the_class_they_were_created_from() {
+        let class = MockClass::new("com.example.Point").constructor(|_| MockValue::object());
+        let class = MockValue::Class(register_class(class)).into_ptr();
+        let point = unsafe { MockValue::from_ptr(new_instance(class, &[])) };
+        assert_eq!(point.class().unwrap().name, "com.example.Point");
+        assert!(point.to_string().starts_with("com.example.Point@"));
+    }
+
+    #[test]
+    fn substitutes_like_java_when_encoding() {
+        let mut buffer = [0xff; 8];
+        let written = as_string("a\u{20ac}", &mut buffer, b"ISO-8859-1\0");
+        assert_eq!(&buffer[..written + 1], b"a?\0");
+    }
+
+    #[test]
+    fn leaves_a_full_buffer_unterminated() {
+        let mut buffer = [0xff; 2];
+        let written = as_string("abc", &mut buffer, b"UTF-8\0");
+        assert_eq!((written, buffer), (2, *b"ab"));
+    }
+
+    #[test]
+    fn decodes_malformed_input_like_java() {
+        let value: PolyglotValue = strings::decode(b"a\xff", Charset::Iso8859_1).unwrap();
+        assert_eq!(value.to_rust_string().unwrap(), "a\u{ff}");
+        let utf8 = CStr::from_bytes_with_nul(b"UTF-8\0").unwrap();
+        let value = unsafe {
+            crate::polyglot::polyglot_from_string_n(b"a\xff".as_ptr() as *const i8, 2, utf8.as_ptr())
+        };
+        assert_eq!(unsafe { MockValue::from_ptr(value) }.as_str().unwrap(), "a\u{fffd}");
+    }
+
+    #[test]
+    fn compares_like_java() {
+        let list = |elements: Vec<MockValue>| {
+            MockValue::from(MockObject {
+                elements: Some(elements),
+                ..MockObject::default()
+            })
+        };
+        assert!(MockValue::string("a").java_equals(&MockValue::string("a")));
+        assert!(!MockValue::from(1).java_equals(&MockValue::from(1.0)));
+        assert!(list(vec![1.into()]).java_equals(&list(vec![1.into()])));
+        assert!(!MockValue::array(vec![]).java_equals(&MockValue::array(vec![])));
+        assert_eq!(MockValue::string("ab").java_hash_code(), 97 * 31 + 98);
+        assert_eq!(MockValue::from(true).java_hash_code(), 1231);
+        assert_eq!(list(vec![1.into(), "x".into()]).to_string(), "[1, x]");
+    }
+
+    #[test]
+    fn handles_resolve_until_released() {
+        unsafe {
+            let handle = _graalvm_llvm_create_handle(string_ptr("h"));
+            assert!(_graalvm_llvm_is_handle(handle));
+            let resolved = MockValue::from_ptr(_graalvm_llvm_resolve_handle(handle));
+            assert_eq!(resolved.as_str().unwrap(), "h");
+            _graalvm_llvm_release_handle(handle);
+            assert!(!_graalvm_llvm_is_handle(handle));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "java.lang.ArrayIndexOutOfBoundsException: 3")]
+    fn throws_java_exceptions_as_panics() {
+        let array = MockValue::array(vec![]).into_ptr();
+        unsafe { set_array_element(array, 3, Arg::Int(1)) };
+    }
+}
@@ -0,0 +1,897 @@
+use core::cell::Cell;
+use core::convert::TryFrom;
+use core::fmt;
+use core::marker::PhantomData;
+use std::ffi::CString;
+use std::thread::LocalKey;
+
+use crate::types::JavaChar;
+
+include!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/bindings.rs"));
+
+pub mod object;
+pub mod strings;
+mod args;
+mod eval;
+mod globals;
+mod handle;
+mod jref;
+#[cfg(any(test, feature = "mock-runtime"))]
+pub mod mock;
+
+pub use args::{args, Args};
+pub use eval::{eval, eval_file, EvalError, Language};
+pub use globals::{export, import, try_import, ImportError};
+pub use handle::Handle;
+pub use jref::{JRef, ReadOnly};
+pub use object::JavaClass;
+
+macro_rules! primitive_receive {
+    ($typename: ident, $graalfn:ident, $assertfn:ident) => {
+        unsafe impl Receive for $typename {
+            unsafe fn from_polyglot_value(value: *mut Value) -> Self {
+                unsafe {
+                    debug_assert!($assertfn(value));
+                    $graalfn(value)
+                }
+            }
+        }
+
+        unsafe impl TryReceive for $typename {
+            unsafe fn try_from_polyglot_value(value: *mut Value) -> Result<Self, ConversionError> {
+                unsafe {
+                    if $assertfn(value) {
+                        Ok($graalfn(value))
+                    } else {
+                        Err(ConversionError::new(stringify!($typename), value))
+                    }
+                }
+            }
+        }
+    };
+
+    ($typename: ident, $graalfn: ident) => {
+        impl Receive for $typename {
+            unsafe fn from_polyglot_value(value: *mut Value) -> Self {
+                unsafe { $graalfn(value) }
+            }
+        }
+    };
+}
+
+macro_rules! pass_and_passable {
+    ($typename: ty) => {
+        unsafe impl Passable for $typename {
+            #[cfg(any(test, feature = "mock-runtime"))]
+            fn into_mock_arg(self) -> mock::Arg {
+                mock::Arg::from(self)
+            }
+        }
+        unsafe impl Pass<$typename> for $typename {
+            fn pass(&self) -> Self {
+                *self
+            }
+        }
+    };
+}
+
+/// C promotes variadic arguments narrower than `int` or `double`, and Rust refuses to pass them
+/// unpromoted, so these types are passed as the wider type.
+macro_rules! promoted_pass {
+    ($typename: ty, $promoted: ty) => {
+        unsafe impl Pass<$promoted> for $typename {
+            fn pass(&self) -> $promoted {
+                *self as $promoted
+            }
+        }
+    };
+}
+
+/// Rust integer types with no Java equivalent are passed as the nearest Java primitive that can
+/// hold them, and panic if the value doesn't fit.
+macro_rules! checked_pass {
+    ($typename: ty, $java: ty) => {
+        unsafe impl Pass<$java> for $typename {
+            fn pass(&self) -> $java {
+                <$java>::try_from(*self).unwrap_or_else(|_| {
+                    panic!(
+                        "{} {} does not fit in {}",
+                        stringify!($typename),
+                        self,
+                        stringify!($java)
+                    )
+                })
+            }
+        }
+    };
+}
+
+/// Receives the nearest Java primitive and fails if it's negative or out of range for the type.
+macro_rules! checked_receive {
+    ($typename: ty, $java: ty) => {
+        unsafe impl Receive for $typename {
+            unsafe fn from_polyglot_value(value: *mut Value) -> Self {
+                match Self::try_from_polyglot_value(value) {
+                    Ok(value) => value,
+                    Err(e) => panic!("{}", e),
+                }
+            }
+        }
+
+        unsafe impl TryReceive for $typename {
+            unsafe fn try_from_polyglot_value(value: *mut Value) -> Result<Self, ConversionError> {
+                <$java>::try_from_polyglot_value(value)
+                    .ok()
+                    .and_then(|java| <$typename>::try_from(java).ok())
+                    .ok_or_else(|| ConversionError::new(stringify!($typename), value))
+            }
+        }
+    };
+}
+
+trait __never_trait {
+    type Output;
+}
+
+#[repr(C)]
+pub struct Value {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct Constructor {
+    _private: [u8; 0],
+}
+
+/// Receive indicates that this type can be received by Rust from the GraalVM Runtime.  
+///
+/// # Safety
+/// Implementations may pass the value to the polyglot runtime, and wrappers that hold on to it
+/// must pass it back unchanged from `Pass`.
+pub unsafe trait Receive {
+    /// # Safety
+    /// `value` must be a polyglot value, or null, in the current GraalVM context.
+    unsafe fn from_polyglot_value(value: *mut Value) -> Self;
+}
+
+/// TryReceive is a checked version of `Receive`, for when the value received may not be
+/// representable as this type.  `from_polyglot_value` only checks this in debug builds;  in release
+/// builds, a Java `long` received as an `i32` is silently truncated.
+///
+/// # Safety
+/// The same as for `Receive`, and a value that's received successfully must be one that
+/// `from_polyglot_value` would have received the same way.
+pub unsafe trait TryReceive: Receive + Sized {
+    /// # Safety
+    /// `value` must be a polyglot value, or null, in the current GraalVM context.
+    unsafe fn try_from_polyglot_value(value: *mut Value) -> Result<Self, ConversionError>;
+}
+
+/// The kind of a polyglot value, as reported by the `polyglot_is_*` functions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueKind {
+    Null,
+    Boolean,
+    Number,
+    String,
+    Object,
+}
+
+impl ValueKind {
+    pub fn of<T: Pass<*mut Value>>(value: &T) -> Self {
+        let value = value.pass();
+        unsafe {
+            if polyglot_is_null(value) {
+                ValueKind::Null
+            } else if polyglot_is_boolean(value) {
+                ValueKind::Boolean
+            } else if polyglot_is_number(value) {
+                ValueKind::Number
+            } else if polyglot_is_string(value) {
+                ValueKind::String
+            } else {
+                ValueKind::Object
+            }
+        }
+    }
+}
+
+impl fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ValueKind::Null => "null",
+            ValueKind::Boolean => "boolean",
+            ValueKind::Number => "number",
+            ValueKind::String => "string",
+            ValueKind::Object => "object",
+        })
+    }
+}
+
+/// A polyglot value could not be converted to the expected Rust type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConversionError {
+    pub expected: &'static str,
+    pub actual: ValueKind,
+}
+
+impl ConversionError {
+    /// # Safety
+    /// `value` must be a polyglot value, or null, in the current GraalVM context.
+    pub unsafe fn new(expected: &'static str, value: *mut Value) -> Self {
+        Self {
+            expected,
+            actual: ValueKind::of(&value),
+        }
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const NUMBER_TYPES: &[&str] = &[
+            "i8", "i16", "i32", "i64", "isize", "u8", "u16", "u32", "u64", "usize", "f32", "f64",
+            "JavaChar",
+        ];
+        match self.actual {
+            ValueKind::Number if NUMBER_TYPES.contains(&self.expected) => {
+                write!(f, "number does not fit in {}", self.expected)
+            }
+            actual => write!(f, "expected {}, found {}", self.expected, actual),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Converts the return value of a generated method, panicking with the method's name if it can't
+/// be converted.
+///
+/// # Safety
+/// `value` must be a polyglot value, or null, in the current GraalVM context.
+pub unsafe fn expect_receive<T: TryReceive>(value: *mut Value, method: &str) -> T {
+    match T::try_from_polyglot_value(value) {
+        Ok(value) => value,
+        Err(e) => panic!("{}(): {}", method, e),
+    }
+}
+
+primitive_receive!(i8, polyglot_as_i8, polyglot_fits_in_i8);
+primitive_receive!(i16, polyglot_as_i16, polyglot_fits_in_i16);
+primitive_receive!(i32, polyglot_as_i32, polyglot_fits_in_i32);
+primitive_receive!(i64, polyglot_as_i64, polyglot_fits_in_i64);
+primitive_receive!(f32, polyglot_as_float, polyglot_fits_in_float);
+primitive_receive!(f64, polyglot_as_double, polyglot_fits_in_double);
+primitive_receive!(bool, polyglot_as_boolean, polyglot_is_boolean);
+checked_receive!(u8, i16);
+checked_receive!(u16, i32);
+checked_receive!(u32, i64);
+checked_receive!(u64, i64);
+checked_receive!(usize, i64);
+checked_receive!(isize, i64);
+
+/// Pass is a marker trait that indicates a type can safely be passed to the GraalVM Runtime.
+///
+/// # Safety
+/// `pass` must return a number, or a polyglot value or null in the current GraalVM context.
+pub unsafe trait Pass<T>
+    where
+        T: Passable,
+{
+    fn pass(&self) -> T;
+}
+
+/// A value that can be passed to Graal Polyglot.  This is either a number or a pointer to a polyglot value
+///
+/// # Safety
+/// Only types that the polyglot functions accept as variadic arguments may implement it.
+pub unsafe trait Passable {
+    /// The mock runtime can't define variadic functions, so it receives arguments as `mock::Arg`s.
+    #[cfg(any(test, feature = "mock-runtime"))]
+    #[doc(hidden)]
+    fn into_mock_arg(self) -> mock::Arg;
+}
+
+pass_and_passable!(*const Value);
+pass_and_passable!(*mut Value);
+pass_and_passable!(i32);
+pass_and_passable!(i64);
+pass_and_passable!(f64);
+promoted_pass!(i8, i32);
+promoted_pass!(i16, i32);
+promoted_pass!(f32, f64);
+checked_pass!(u8, i32);
+checked_pass!(u16, i32);
+checked_pass!(u32, i64);
+checked_pass!(u64, i64);
+checked_pass!(usize, i64);
+checked_pass!(isize, i64);
+
+thread_local! {
+    static BOOLEAN_TRUE: Cell<*mut Value> = const { Cell::new(core::ptr::null_mut()) };
+    static BOOLEAN_FALSE: Cell<*mut Value> = const { Cell::new(core::ptr::null_mut()) };
+    static CHARACTER: Cell<*mut Value> = const { Cell::new(core::ptr::null_mut()) };
+}
+
+/// Resolves a value that's looked up once per thread.  Managed values can't be kept in native
+/// memory, so the cache holds a handle to it, which is created again if it was released, like by
+/// `mock::reset`.
+fn cached(cache: &'static LocalKey<Cell<*mut Value>>, lookup: impl FnOnce() -> *mut Value) -> *mut Value {
+    cache.with(|handle| unsafe {
+        if !_graalvm_llvm_is_handle(handle.get()) {
+            handle.set(_graalvm_llvm_create_handle(lookup()));
+        }
+        _graalvm_llvm_resolve_handle(handle.get())
+    })
+}
+
+/// A static member of a Java class, like `java.lang.Boolean.TRUE`.
+fn static_member(class: &str, name: &str) -> *mut Value {
+    unsafe {
+        let class = polyglot_java_type(make_cstr(class).as_ptr());
+        polyglot_get_member(class, make_cstr(name).as_ptr())
+    }
+}
+
+/// `bool` is promoted to `int` when passed as a variadic argument, which Java won't accept for a
+/// `boolean` parameter, so it's passed as `java.lang.Boolean` and unboxed on the Java side.
+unsafe impl Pass<*mut Value> for bool {
+    fn pass(&self) -> *mut Value {
+        if *self {
+            cached(&BOOLEAN_TRUE, || static_member("java.lang.Boolean", "TRUE"))
+        } else {
+            cached(&BOOLEAN_FALSE, || static_member("java.lang.Boolean", "FALSE"))
+        }
+    }
+}
+
+fn character_class() -> *mut Value {
+    cached(&CHARACTER, || unsafe {
+        polyglot_java_type(make_cstr("java.lang.Character").as_ptr())
+    })
+}
+
+/// A Java `char` is passed as a `java.lang.Character`, boxed from its code unit with
+/// `Character.valueOf`, so Java gets it as is even if it's half of a surrogate pair.
+unsafe impl Pass<*mut Value> for JavaChar {
+    fn pass(&self) -> *mut Value {
+        crate::invoke_method!(character_class(), "valueOf", self.0 as i32)
+    }
+}
+
+unsafe impl Receive for JavaChar {
+    unsafe fn from_polyglot_value(value: *mut Value) -> Self {
+        match Self::try_from_polyglot_value(value) {
+            Ok(c) => c,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+/// Java `char`s reach Rust as single-character strings, and sometimes as numbers.  The string isn't
+/// encoded, since encoding replaces a lone surrogate;  `Character.hashCode(char)` is its code unit.
+unsafe impl TryReceive for JavaChar {
+    unsafe fn try_from_polyglot_value(value: *mut Value) -> Result<Self, ConversionError> {
+        let error = || ConversionError::new("JavaChar", value);
+        if unsafe { polyglot_fits_in_i32(value) } {
+            u16::try_from(unsafe { polyglot_as_i32(value) })
+                .map(JavaChar)
+                .map_err(|_| error())
+        } else if unsafe { polyglot_is_string(value) && polyglot_get_string_size(value) == 1 } {
+            let unit = crate::invoke_method!(character_class(), "hashCode", value);
+            Ok(JavaChar(unsafe { polyglot_as_i32(unit) } as u16))
+        } else {
+            Err(error())
+        }
+    }
+}
+
+/// `null` is received as `None`.  `None` is passed as a null pointer, which Java receives as
+/// `null`.
+unsafe impl<T> Pass<*mut Value> for Option<T>
+    where
+        T: Pass<*mut Value>,
+{
+    fn pass(&self) -> *mut Value {
+        match self {
+            Some(value) => value.pass(),
+            None => std::ptr::null_mut(),
+        }
+    }
+}
+
+unsafe impl<T: Receive> Receive for Option<T> {
+    unsafe fn from_polyglot_value(value: *mut Value) -> Self {
+        if unsafe { polyglot_is_null(value) } {
+            None
+        } else {
+            Some(T::from_polyglot_value(value))
+        }
+    }
+}
+
+unsafe impl<T: TryReceive> TryReceive for Option<T> {
+    unsafe fn try_from_polyglot_value(value: *mut Value) -> Result<Self, ConversionError> {
+        if unsafe { polyglot_is_null(value) } {
+            Ok(None)
+        } else {
+            T::try_from_polyglot_value(value).map(Some)
+        }
+    }
+}
+
+pub struct JavaArray<T, U>
+    where
+        T: Pass<U> + Receive,
+        U: Passable,
+{
+    ptr: *mut Value,
+    phantom: PhantomData<T>,
+    phantom2: PhantomData<U>,
+}
+
+unsafe impl<T, U> Pass<*mut Value> for JavaArray<T, U>
+    where
+        T: Pass<U> + Receive,
+        U: Passable,
+{
+    fn pass(&self) -> *mut Value {
+        self.ptr
+    }
+}
+
+unsafe impl<T, U> Receive for JavaArray<T, U>
+    where
+        T: Pass<U> + Receive,
+        U: Passable,
+{
+    unsafe fn from_polyglot_value(value: *mut Value) -> Self {
+        Self {
+            ptr: value,
+            phantom: PhantomData,
+            phantom2: PhantomData,
+        }
+    }
+}
+
+unsafe impl<T, U> TryReceive for JavaArray<T, U>
+    where
+        T: Pass<U> + Receive,
+        U: Passable,
+{
+    /// Rejects null, which `Option<JavaArray<T, U>>` receives, and values without array elements.
+    /// The elements are converted when they're read.
+    unsafe fn try_from_polyglot_value(value: *mut Value) -> Result<Self, ConversionError> {
+        if unsafe { polyglot_is_null(value) || !polyglot_has_array_elements(value) } {
+            return Err(ConversionError::new("JavaArray", value));
+        }
+        Ok(Self::from_polyglot_value(value))
+    }
+}
+
+impl<T, U> JavaArray<T, U>
+    where
+        T: Pass<U> + Receive,
+        U: Passable,
+{
+    /// Creates another read-only reference to the same array.
+    pub fn clone_ref(&self) -> ReadOnly<Self> {
+        unsafe { ReadOnly::from_polyglot_value(self.ptr) }
+    }
+
+    pub fn get(&self, index: u64) -> Option<T> {
+        unsafe {
+            if index >= polyglot_get_array_size(self.ptr) {
+                None
+            } else {
+                Some(T::from_polyglot_value(polyglot_get_array_element(
+                    self.ptr,
+                    index as i32,
+                )))
+            }
+        }
+    }
+}
+
+pub fn expect_variadic<U: Passable, T: Pass<U>>(value: T) -> U {
+    value.pass()
+}
+
+/// these macros were taken from https://github.com/ruestigraben/ruesti-base/blob/master/src/main/rust/polyglot.rs
+#[macro_export]
+macro_rules! new_instance {
+    ($constructor: expr $(, $args: expr)*) => {{
+        let constructor = $constructor as *mut $crate::polyglot::Value;
+        let args = ($($args,)*);
+        // `java_type` only returns polyglot values that can be instantiated.
+        unsafe { $crate::polyglot::PolyglotArgs::new_instance(args, constructor) }
+    }};
+}
+
+#[macro_export]
+macro_rules! invoke_method {
+    ($value: expr, $method: expr $(, $args: expr)*) => {{
+        let value: *mut $crate::polyglot::Value = $value;
+        let method = $crate::polyglot::make_cstr($method);
+        let args = ($($args,)*);
+        unsafe { $crate::polyglot::PolyglotArgs::invoke(args, value, &method) }
+    }};
+}
+
+pub fn make_cstr(name: &str) -> CString {
+    CString::new(name).unwrap()
+}
+
+pub fn java_type(name: &str) -> *mut Constructor {
+    let c_str = make_cstr(name);
+    let value = unsafe { polyglot_java_type(c_str.as_ptr()) };
+    if unsafe { polyglot_is_null(value) } || !unsafe { polyglot_can_instantiate(value) } {
+        panic!("Not a type")
+    }
+    value as *mut Constructor
+}
+
+/// A polyglot value of any type, for handling values that don't have a binding, like the results
+/// of scripts in other languages.  Unlike the functions in `bindings.rs`, all of its methods are
+/// safe to call.
+pub struct PolyglotValue {
+    ptr: *mut Value,
+}
+
+unsafe impl Pass<*mut Value> for PolyglotValue {
+    fn pass(&self) -> *mut Value {
+        self.ptr
+    }
+}
+
+unsafe impl Receive for PolyglotValue {
+    unsafe fn from_polyglot_value(value: *mut Value) -> Self {
+        Self { ptr: value }
+    }
+}
+
+unsafe impl TryReceive for PolyglotValue {
+    unsafe fn try_from_polyglot_value(value: *mut Value) -> Result<Self, ConversionError> {
+        Ok(Self::from_polyglot_value(value))
+    }
+}
+
+impl PolyglotValue {
+    /// Creates another read-only reference to the same value.
+    pub fn clone_ref(&self) -> ReadOnly<Self> {
+        unsafe { ReadOnly::from_polyglot_value(self.ptr) }
+    }
+
+    pub fn kind(&self) -> ValueKind {
+        ValueKind::of(self)
+    }
+
+    pub fn is_null(&self) -> bool {
+        unsafe { polyglot_is_null(self.ptr) }
+    }
+
+    pub fn is_number(&self) -> bool {
+        unsafe { polyglot_is_number(self.ptr) }
+    }
+
+    pub fn is_boolean(&self) -> bool {
+        unsafe { polyglot_is_boolean(self.ptr) }
+    }
+
+    pub fn is_string(&self) -> bool {
+        unsafe { polyglot_is_string(self.ptr) }
+    }
+
+    /// Converts the value to a Rust type, checking that it can be represented as that type.
+    pub fn receive<T: TryReceive>(&self) -> Result<T, ConversionError> {
+        unsafe { T::try_from_polyglot_value(self.ptr) }
+    }
+
+    pub fn to_rust_string(&self) -> Result<std::string::String, strings::StringError> {
+        strings::to_rust_string(self)
+    }
+
+    pub fn has_members(&self) -> bool {
+        unsafe { polyglot_has_members(self.ptr) }
+    }
+
+    pub fn has_member(&self, name: &str) -> bool {
+        unsafe { polyglot_has_member(self.ptr, make_cstr(name).as_ptr()) }
+    }
+
+    /// Reads a member of the value, or `None` if it doesn't have one called `name`.
+    pub fn get_member(&self, name: &str) -> Option<PolyglotValue> {
+        if !self.has_member(name) {
+            return None;
+        }
+        let member = unsafe { polyglot_get_member(self.ptr, make_cstr(name).as_ptr()) };
+        Some(unsafe { Self::from_polyglot_value(member) })
+    }
+
+    pub fn put_member<U: Passable, T: Pass<U>>(&self, name: &str, value: T) {
+        let name = make_cstr(name);
+        #[cfg(not(any(test, feature = "mock-runtime")))]
+        unsafe { polyglot_put_member(self.ptr, name.as_ptr(), value.pass()) }
+        #[cfg(any(test, feature = "mock-runtime"))]
+        unsafe { mock::put_member(self.ptr, name.as_ptr(), value.pass().into_mock_arg()) }
+    }
+
+    /// Removes a member of the value, returning whether it was removed.
+    pub fn remove_member(&self, name: &str) -> bool {
+        unsafe { polyglot_remove_member(self.ptr, make_cstr(name).as_ptr()) }
+    }
+
+    pub fn has_array_elements(&self) -> bool {
+        unsafe { polyglot_has_array_elements(self.ptr) }
+    }
+
+    /// The number of array elements, or 0 if the value isn't an array.
+    pub fn array_len(&self) -> u64 {
+        if self.has_array_elements() {
+            unsafe { polyglot_get_array_size(self.ptr) }
+        } else {
+            0
+        }
+    }
+
+    /// Reads an array element, or `None` if the index is out of bounds.
+    pub fn get_element(&self, index: u64) -> Option<PolyglotValue> {
+        if index >= self.array_len() {
+            return None;
+        }
+        let element = unsafe { polyglot_get_array_element(self.ptr, index as i32) };
+        Some(unsafe { Self::from_polyglot_value(element) })
+    }
+
+    pub fn set_element<U: Passable, T: Pass<U>>(&self, index: u64, value: T) {
+        #[cfg(not(any(test, feature = "mock-runtime")))]
+        unsafe { polyglot_set_array_element(self.ptr, index as i32, value.pass()) }
+        #[cfg(any(test, feature = "mock-runtime"))]
+        unsafe { mock::set_array_element(self.ptr, index as i32, value.pass().into_mock_arg()) }
+    }
+
+    /// Removes an array element, returning whether it was removed.
+    pub fn remove_element(&self, index: u64) -> bool {
+        unsafe { polyglot_remove_array_element(self.ptr, index as i32) }
+    }
+
+    pub fn can_execute(&self) -> bool {
+        unsafe { polyglot_can_execute(self.ptr) }
+    }
+
+    pub fn can_instantiate(&self) -> bool {
+        unsafe { polyglot_can_instantiate(self.ptr) }
+    }
+
+    /// Invokes the method `name` on the value.  The arguments are passed as a tuple, like
+    /// `value.invoke("substring", (0, 5))`;  use `()` for no arguments and `(x,)` for one.
+    pub fn invoke<P, A: PolyglotArgs<P>>(&self, name: &str, args: A) -> PolyglotValue {
+        let name = make_cstr(name);
+        unsafe { Self::from_polyglot_value(args.invoke(self.ptr, &name)) }
+    }
+
+    /// Instantiates the value, which should be a class or constructor, with a tuple of arguments.
+    pub fn new_instance<P, A: PolyglotArgs<P>>(&self, args: A) -> PolyglotValue {
+        unsafe { Self::from_polyglot_value(args.new_instance(self.ptr)) }
+    }
+}
+
+/// A tuple of arguments to pass to a polyglot method or constructor.  `P` is the tuple of the
+/// `Passable` types the arguments are passed as.  `invoke_method!` and `new_instance!` use it too.
+pub trait PolyglotArgs<P> {
+    /// # Safety
+    /// `object` must be a polyglot value in the current GraalVM context.
+    unsafe fn invoke(self, object: *mut Value, name: &CString) -> *mut Value;
+    /// # Safety
+    /// `constructor` must be a polyglot value in the current GraalVM context.
+    unsafe fn new_instance(self, constructor: *mut Value) -> *mut Value;
+}
+
+macro_rules! polyglot_args {
+    ($($arg: ident $passable: ident $name: ident),*) => {
+        impl<$($arg, $passable),*> PolyglotArgs<($($passable,)*)> for ($($arg,)*)
+            where
+                $($arg: Pass<$passable>, $passable: Passable),*
+        {
+            #[cfg(not(any(test, feature = "mock-runtime")))]
+            unsafe fn invoke(self, object: *mut Value, name: &CString) -> *mut Value {
+                let ($($name,)*) = self;
+                polyglot_invoke(object, name.as_ptr(), $($name.pass()),*)
+            }
+
+            #[cfg(not(any(test, feature = "mock-runtime")))]
+            unsafe fn new_instance(self, constructor: *mut Value) -> *mut Value {
+                let ($($name,)*) = self;
+                polyglot_new_instance(constructor, $($name.pass()),*)
+            }
+
+            #[cfg(any(test, feature = "mock-runtime"))]
+            unsafe fn invoke(self, object: *mut Value, name: &CString) -> *mut Value {
+                let ($($name,)*) = self;
+                mock::invoke(object, name.as_ptr(), &[$($name.pass().into_mock_arg()),*])
+            }
+
+            #[cfg(any(test, feature = "mock-runtime"))]
+            unsafe fn new_instance(self, constructor: *mut Value) -> *mut Value {
+                let ($($name,)*) = self;
+                mock::new_instance(constructor, &[$($name.pass().into_mock_arg()),*])
+            }
+        }
+    };
+}
+
+polyglot_args!();
+polyglot_args!(A AP a);
+polyglot_args!(A AP a, B BP b);
+polyglot_args!(A AP a, B BP b, C CP c);
+polyglot_args!(A AP a, B BP b, C CP c, D DP d);
+polyglot_args!(A AP a, B BP b, C CP c, D DP d, E EP e);
+polyglot_args!(A AP a, B BP b, C CP c, D DP d, E EP e, F FP f);
+polyglot_args!(A AP a, B BP b, C CP c, D DP d, E EP e, F FP f, G GP g);
+polyglot_args!(A AP a, B BP b, C CP c, D DP d, E EP e, F FP f, G GP g, H HP h);
+polyglot_args!(A AP a, B BP b, C CP c, D DP d, E EP e, F FP f, G GP g, H HP h, I IP i);
+polyglot_args!(A AP a, B BP b, C CP c, D DP d, E EP e, F FP f, G GP g, H HP h, I IP i, J JP j);
+polyglot_args!(A AP a, B BP b, C CP c, D DP d, E EP e, F FP f, G GP g, H HP h, I IP i, J JP j, K KP k);
+polyglot_args!(A AP a, B BP b, C CP c, D DP d, E EP e, F FP f, G GP g, H HP h, I IP i, J JP j, K KP k, L LP l);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mock::{MockObject, MockValue};
+
+    fn value(value: impl Into<MockValue>) -> PolyglotValue {
+        value.into().receive()
+    }
+
+    #[test]
+    fn receives_primitives_that_fit() {
+        assert_eq!(value(7).receive::<i8>(), Ok(7));
+        assert_eq!(value(1i64 << 40).receive::<i64>(), Ok(1 << 40));
+        assert_eq!(value(1.5).receive::<f64>(), Ok(1.5));
+        assert_eq!(value(2.0).receive::<i32>(), Ok(2));
+        assert_eq!(value(true).receive::<bool>(), Ok(true));
+    }
+
+    #[test]
+    fn rejects_primitives_that_do_not_fit() {
+        let error = value(1i64 << 40).receive::<i32>().unwrap_err();
+        assert_eq!(error.actual, ValueKind::Number);
+        assert_eq!(error.to_string(), "number does not fit in i32");
+        assert!(value(1.5).receive::<i64>().is_err());
+        let error = value("1").receive::<i32>().unwrap_err();
+        assert_eq!(error.to_string(), "expected i32, found string");
+        let error = value(MockValue::Null).receive::<bool>().unwrap_err();
+        assert_eq!(error.to_string(), "expected bool, found null");
+    }
+
+    #[test]
+    fn range_checks_unsigned_types() {
+        assert_eq!(value(255).receive::<u8>(), Ok(255));
+        assert!(value(256).receive::<u8>().is_err());
+        assert!(value(-1).receive::<u32>().is_err());
+        assert_eq!(value(1i64 << 40).receive::<usize>(), Ok(1 << 40));
+        assert!(value(-1).receive::<usize>().is_err());
+        assert_eq!(Pass::<i64>::pass(&u32::MAX), u32::MAX as i64);
+    }
+
+    #[test]
+    #[should_panic(expected = "u64 18446744073709551615 does not fit in i64")]
+    fn passing_an_unsigned_value_that_does_not_fit_panics() {
+        Pass::<i64>::pass(&u64::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "size(): number does not fit in i8")]
+    fn expect_receive_names_the_method() {
+        unsafe { expect_receive::<i8>(MockValue::from(300).into_ptr(), "size") };
+    }
+
+    #[test]
+    fn receives_null_as_none() {
+        assert_eq!(value(MockValue::Null).receive::<Option<i32>>(), Ok(None));
+        assert_eq!(value(3).receive::<Option<i32>>(), Ok(Some(3)));
+        let none: Option<PolyglotValue> = None;
+        assert!(unsafe { polyglot_is_null(none.pass()) });
+    }
+
+    #[test]
+    fn passes_booleans_as_java_booleans() {
+        let passed = |b: bool| unsafe { MockValue::from_ptr(b.pass()) };
+        assert!(matches!(passed(true), MockValue::Boolean(true)));
+        assert!(matches!(passed(false), MockValue::Boolean(false)));
+    }
+
+    #[test]
+    fn passes_the_same_booleans_after_a_reset() {
+        let passed = |b: bool| unsafe { MockValue::from_ptr(b.pass()) };
+        assert!(matches!(passed(true), MockValue::Boolean(true)));
+        mock::reset();
+        assert!(matches!(passed(true), MockValue::Boolean(true)));
+        assert!(matches!(passed(false), MockValue::Boolean(false)));
+    }
+
+    #[test]
+    fn passes_and_receives_chars_as_code_units() {
+        for &unit in &[0x41, 0xe9, 0xd83d, 0xde00, 0xffff] {
+            let c = JavaChar(unit);
+            let passed = unsafe { MockValue::from_ptr(c.pass()) };
+            assert!(matches!(&passed, MockValue::String(units) if **units == [unit]));
+            assert_eq!(value(passed).receive::<JavaChar>(), Ok(c));
+        }
+        assert_eq!(value(0xe9).receive::<JavaChar>(), Ok(JavaChar(0xe9)));
+        assert!(value(-1).receive::<JavaChar>().is_err());
+        assert!(value("ab").receive::<JavaChar>().is_err());
+    }
+
+    #[test]
+    fn reports_value_kinds() {
+        assert_eq!(value(MockValue::Null).kind(), ValueKind::Null);
+        assert_eq!(value(false).kind(), ValueKind::Boolean);
+        assert_eq!(value(1.5).kind(), ValueKind::Number);
+        assert_eq!(value("s").kind(), ValueKind::String);
+        assert_eq!(value(MockValue::object()).kind(), ValueKind::Object);
+    }
+
+    #[test]
+    fn reads_and_writes_members() {
+        let object = value(MockValue::object());
+        assert!(object.has_members());
+        assert!(object.get_member("name").is_none());
+        object.put_member("name", strings::from_str::<PolyglotValue>("x").pass());
+        object.put_member("count", 3);
+        assert!(object.has_member("name"));
+        assert_eq!(object.get_member("count").unwrap().receive::<i32>(), Ok(3));
+        assert!(object.remove_member("count"));
+        assert!(!object.remove_member("count"));
+    }
+
+    #[test]
+    fn reads_and_writes_array_elements() {
+        let array = value(MockValue::array(vec![MockValue::from(1), MockValue::from(2)]));
+        assert_eq!(array.array_len(), 2);
+        array.set_element(1, 5);
+        assert_eq!(array.get_element(1).unwrap().receive::<i32>(), Ok(5));
+        assert!(array.get_element(2).is_none());
+        assert_eq!(value(1).array_len(), 0);
+
+        let list = value(MockValue::from(MockObject {
+            elements: Some(vec![MockValue::from(1)]),
+            ..MockObject::default()
+        }));
+        assert!(list.remove_element(0));
+        assert_eq!(list.array_len(), 0);
+    }
+
+    #[test]
+    fn invokes_methods_and_constructors() {
+        let list_class = value(MockValue::Class(mock::find_class("java.util.ArrayList").unwrap()));
+        assert!(list_class.can_instantiate());
+        let list = list_class.new_instance(());
+        list.invoke("add", (4,));
+        list.invoke("add", (0, 3));
+        assert_eq!(list.invoke("size", ()).receive::<i32>(), Ok(2));
+        assert_eq!(list.invoke("get", (0,)).receive::<i32>(), Ok(3));
+        let hello = strings::from_str::<PolyglotValue>("hello");
+        let hell = hello.invoke("substring", (0, 4));
+        assert_eq!(hell.to_rust_string().unwrap(), "hell");
+    }
+
+    #[test]
+    fn reads_java_arrays() {
+        let array: JavaArray<i32, i32> =
+            MockValue::array(vec![MockValue::from(1), MockValue::from(2)]).receive();
+        assert_eq!(array.get(1), Some(2));
+        assert_eq!(array.get(2), None);
+    }
+
+    #[test]
+    fn receiving_an_array_checks_the_value() {
+        let array = value(MockValue::array(vec![])).receive::<JavaArray<i32, i32>>();
+        assert!(array.is_ok());
+        let array = value(MockValue::object()).receive::<JavaArray<i32, i32>>();
+        assert_eq!(array.err().unwrap().to_string(), "expected JavaArray, found object");
+        assert!(value(MockValue::Null).receive::<JavaArray<i32, i32>>().is_err());
+        assert!(value("[]").receive::<JavaArray<i32, i32>>().is_err());
+        let array = value(MockValue::Null).receive::<Option<JavaArray<i32, i32>>>();
+        assert!(array.unwrap().is_none());
+    }
+}
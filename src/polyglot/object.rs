@@ -0,0 +1,122 @@
+//! Java's `Object` methods, which `class!` implements Rust's standard traits with:  `toString` for
+//! `Display`, and with the class name for `Debug`, `equals` for `PartialEq` and `Eq`, `hashCode`
+//! for `Hash`, and `compareTo` for `PartialOrd` and `Ord` if the class implements `Comparable`.  It also has
+//! `JavaClass`, which `class!` implements so objects can be checked against the class with
+//! `isInstance`.
+
+use core::cmp::Ordering;
+use core::fmt;
+
+use super::{
+    expect_receive, make_cstr, polyglot_get_member, polyglot_is_null, polyglot_java_type, strings,
+    ConversionError, Pass, PolyglotValue, Receive, Value, ValueKind,
+};
+
+/// A binding for a Java class, which `class!` implements.  Checked downcasts like
+/// `Object::downcast` use it to find the class.
+pub trait JavaClass: Receive {
+    /// The fully qualified name, like `java.util.ArrayList`.
+    const CLASS_NAME: &'static str;
+}
+
+/// The check `class!`'s `TryReceive` makes:  `value` isn't null, which `Option` receives, and it's
+/// an object, or a value like a string or a number that is an instance of the class in Java.  The
+/// class of an object isn't checked, since that would call into Java for every value received;
+/// `Object::downcast` does.
+///
+/// # Safety
+/// `value` must be a polyglot value, or null, in the current GraalVM context.
+pub unsafe fn check_reference(
+    value: *mut Value,
+    class_name: &'static str,
+) -> Result<(), ConversionError> {
+    match ValueKind::of(&value) {
+        ValueKind::Object => Ok(()),
+        ValueKind::Null => Err(ConversionError::new(class_name, value)),
+        // Everything else is boxed to an `Object`, without asking Java.
+        _ if class_name == "java.lang.Object" => Ok(()),
+        _ if is_instance(&value, class_name) => Ok(()),
+        _ => Err(ConversionError::new(class_name, value)),
+    }
+}
+
+/// `value.toString()`, or `null` for a null reference like `String.valueOf`.
+pub fn to_string<T: Pass<*mut Value>>(value: &T) -> std::string::String {
+    let value = value.pass();
+    if unsafe { polyglot_is_null(value) } {
+        return "null".to_owned();
+    }
+    let string = crate::invoke_method!(value, "toString");
+    let string: PolyglotValue = unsafe { expect_receive(string, "toString") };
+    strings::to_rust_string(&string)
+        .unwrap_or_else(|e| panic!("toString didn't return a string: {}", e))
+}
+
+/// Formats `value` like a tuple struct named after the class, holding its `toString`:
+/// `java.util.ArrayList("[1, 2]")`, or `java.util.ArrayList(null)` for a null reference.
+pub fn debug<T>(value: &T, f: &mut fmt::Formatter<'_>) -> fmt::Result
+where
+    T: JavaClass + Pass<*mut Value>,
+{
+    let mut tuple = f.debug_tuple(T::CLASS_NAME);
+    if unsafe { polyglot_is_null(value.pass()) } {
+        tuple.field(&format_args!("null"));
+    } else {
+        tuple.field(&to_string(value));
+    }
+    tuple.finish()
+}
+
+/// `a.equals(b)`, where null references are only equal to each other like `Objects.equals`.
+pub fn equals<T: Pass<*mut Value>, U: Pass<*mut Value>>(a: &T, b: &U) -> bool {
+    let (a, b) = (a.pass(), b.pass());
+    match unsafe { (polyglot_is_null(a), polyglot_is_null(b)) } {
+        (false, false) => {
+            let result = crate::invoke_method!(a, "equals", b);
+            unsafe { expect_receive(result, "equals") }
+        }
+        (a_is_null, b_is_null) => a_is_null && b_is_null,
+    }
+}
+
+/// `value.hashCode()`, or 0 for a null reference like `Objects.hashCode`.
+pub fn hash_code<T: Pass<*mut Value>>(value: &T) -> i32 {
+    let value = value.pass();
+    if unsafe { polyglot_is_null(value) } {
+        return 0;
+    }
+    let result = crate::invoke_method!(value, "hashCode");
+    unsafe { expect_receive(result, "hashCode") }
+}
+
+/// `a.compareTo(b)`, which panics if either is a null reference, since Java would throw a
+/// `NullPointerException`.
+pub fn compare_to<T: Pass<*mut Value>, U: Pass<*mut Value>>(a: &T, b: &U) -> Ordering {
+    let (a, b) = (a.pass(), b.pass());
+    if unsafe { polyglot_is_null(a) || polyglot_is_null(b) } {
+        panic!("compareTo called with a null reference");
+    }
+    let result = crate::invoke_method!(a, "compareTo", b);
+    let result: i32 = unsafe { expect_receive(result, "compareTo") };
+    result.cmp(&0)
+}
+
+/// `Class.forName(class_name).isInstance(value)`, which is false for a null reference.  Panics if
+/// there's no such class.  Type arguments are erased, so any `ArrayList` is an instance of every
+/// `ArrayList<E, _>`.
+pub fn is_instance<T: Pass<*mut Value>>(value: &T, class_name: &str) -> bool {
+    let value = value.pass();
+    if unsafe { polyglot_is_null(value) } {
+        return false;
+    }
+    let class = unsafe {
+        let java_type = polyglot_java_type(make_cstr(class_name).as_ptr());
+        if polyglot_is_null(java_type) {
+            panic!("no Java class named {}", class_name);
+        }
+        // The type is the class's static side, and its `class` is the `java.lang.Class`.
+        polyglot_get_member(java_type, make_cstr("class").as_ptr())
+    };
+    let result = crate::invoke_method!(class, "isInstance", value);
+    unsafe { expect_receive(result, "isInstance") }
+}
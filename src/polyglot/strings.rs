@@ -0,0 +1,376 @@
+use core::fmt;
+use std::ffi::CStr;
+
+use super::{
+    polyglot_as_string, polyglot_from_string_n, polyglot_get_string_size, polyglot_is_string, Pass,
+    Receive, Value,
+};
+
+/// A character set that polyglot strings can be converted to and from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Charset {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Iso8859_1,
+    Ascii,
+}
+
+impl Charset {
+    /// The Java name of the character set, as passed to the polyglot runtime.
+    pub fn name(self) -> &'static str {
+        self.c_name().to_str().unwrap()
+    }
+
+    fn c_name(self) -> &'static CStr {
+        let name: &'static [u8] = match self {
+            Charset::Utf8 => b"UTF-8\0",
+            Charset::Utf16Le => b"UTF-16LE\0",
+            Charset::Utf16Be => b"UTF-16BE\0",
+            Charset::Iso8859_1 => b"ISO-8859-1\0",
+            Charset::Ascii => b"US-ASCII\0",
+        };
+        CStr::from_bytes_with_nul(name).unwrap()
+    }
+
+    /// Upper bound on the number of bytes a single character of a polyglot string encodes to.
+    fn max_char_len(self) -> u64 {
+        match self {
+            Charset::Utf8 | Charset::Utf16Le | Charset::Utf16Be => 4,
+            Charset::Iso8859_1 | Charset::Ascii => 1,
+        }
+    }
+
+    /// Width of the zero-terminator `polyglot_as_string` appends, in bytes.
+    fn terminator_len(self) -> u64 {
+        match self {
+            Charset::Utf16Le | Charset::Utf16Be => 2,
+            _ => 1,
+        }
+    }
+
+    /// The largest code point the character set can represent, if it is not all of Unicode.
+    fn max_code_point(self) -> Option<u32> {
+        match self {
+            Charset::Iso8859_1 => Some(0xff),
+            Charset::Ascii => Some(0x7f),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Charset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StringError {
+    /// The polyglot value is not a string.
+    NotAString,
+    /// `character`, at character index `index`, can't be represented in `charset`.
+    Unmappable {
+        charset: Charset,
+        index: usize,
+        character: char,
+    },
+    /// The UTF-16 code unit `unit`, at character index `index`, is a surrogate without its other
+    /// half, so it has no representation in any character set.
+    UnpairedSurrogate {
+        charset: Charset,
+        index: usize,
+        unit: u16,
+    },
+    /// The bytes at `offset` are not valid in `charset`.
+    Malformed { charset: Charset, offset: usize },
+}
+
+impl fmt::Display for StringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StringError::NotAString => write!(f, "polyglot value is not a string"),
+            StringError::Unmappable {
+                charset,
+                index,
+                character,
+            } => write!(
+                f,
+                "character {:?} at index {} cannot be represented in {}",
+                character, index, charset
+            ),
+            StringError::UnpairedSurrogate {
+                charset,
+                index,
+                unit,
+            } => write!(
+                f,
+                "unpaired surrogate {:#06x} at index {} cannot be represented in {}",
+                unit, index, charset
+            ),
+            StringError::Malformed { charset, offset } => {
+                write!(f, "invalid {} sequence at byte {}", charset, offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StringError {}
+
+/// Encodes a polyglot string into bytes in the given character set.
+///
+/// The returned bytes are not zero-terminated.  If a character has no representation in `charset`,
+/// an `Unmappable` error is returned rather than the `?` the Java encoder substitutes, and an
+/// unpaired surrogate is an `UnpairedSurrogate` error for every character set.
+pub fn encode<T: Pass<*mut Value>>(value: &T, charset: Charset) -> Result<Vec<u8>, StringError> {
+    let value = value.pass();
+    if !unsafe { polyglot_is_string(value) } {
+        return Err(StringError::NotAString);
+    }
+
+    let bytes = as_string(value, charset);
+    match charset {
+        Charset::Utf16Le | Charset::Utf16Be => {
+            check_mappable(utf16_units(&bytes, charset), charset)?
+        }
+        _ => {
+            let units = as_string(value, Charset::Utf16Le);
+            check_mappable(utf16_units(&units, Charset::Utf16Le), charset)?
+        }
+    }
+    Ok(bytes)
+}
+
+/// Decodes `bytes` in the given character set into a polyglot string.
+///
+/// Zero bytes are not treated as terminators;  they become part of the string.
+pub fn decode<T: Receive>(bytes: &[u8], charset: Charset) -> Result<T, StringError> {
+    validate(bytes, charset)?;
+    let value = unsafe {
+        polyglot_from_string_n(
+            bytes.as_ptr() as *const i8,
+            bytes.len() as u64,
+            charset.c_name().as_ptr(),
+        )
+    };
+    Ok(unsafe { T::from_polyglot_value(value) })
+}
+
+/// Converts a polyglot string to a Rust `String`.
+pub fn to_rust_string<T: Pass<*mut Value>>(value: &T) -> Result<std::string::String, StringError> {
+    let bytes = encode(value, Charset::Utf8)?;
+    std::string::String::from_utf8(bytes).map_err(|e| StringError::Malformed {
+        charset: Charset::Utf8,
+        offset: e.utf8_error().valid_up_to(),
+    })
+}
+
+/// Converts a Rust string slice to a polyglot string.
+pub fn from_str<T: Receive>(string: &str) -> T {
+    decode(string.as_bytes(), Charset::Utf8).unwrap()
+}
+
+/// Reads the whole string, growing the buffer if the runtime reports that it filled it.
+/// The result of `polyglot_as_string` may not be zero-terminated when the buffer is exactly full,
+/// so only the returned length is trusted.
+fn as_string(value: *mut Value, charset: Charset) -> Vec<u8> {
+    let size = unsafe { polyglot_get_string_size(value) };
+    let mut capacity = size * charset.max_char_len() + charset.terminator_len();
+    loop {
+        let mut buffer = vec![0u8; capacity as usize];
+        let written = unsafe {
+            polyglot_as_string(
+                value,
+                buffer.as_mut_ptr() as *mut i8,
+                capacity,
+                charset.c_name().as_ptr(),
+            )
+        };
+        if written + charset.terminator_len() <= capacity {
+            buffer.truncate(written as usize);
+            return buffer;
+        }
+        capacity *= 2;
+    }
+}
+
+/// The UTF-16 code units of `bytes`, which must be in `Utf16Le` or `Utf16Be`.
+fn utf16_units(bytes: &[u8], charset: Charset) -> impl Iterator<Item = u16> + '_ {
+    bytes.chunks_exact(2).map(move |unit| match charset {
+        Charset::Utf16Le => u16::from_le_bytes([unit[0], unit[1]]),
+        _ => u16::from_be_bytes([unit[0], unit[1]]),
+    })
+}
+
+/// Finds the first character of a string's UTF-16 code units that `charset` can't represent.
+fn check_mappable(units: impl Iterator<Item = u16>, charset: Charset) -> Result<(), StringError> {
+    let max = charset.max_code_point().unwrap_or(u32::MAX);
+    for (index, character) in core::char::decode_utf16(units).enumerate() {
+        let character = match character {
+            Ok(character) => character,
+            Err(e) => {
+                return Err(StringError::UnpairedSurrogate {
+                    charset,
+                    index,
+                    unit: e.unpaired_surrogate(),
+                })
+            }
+        };
+        if character as u32 > max {
+            return Err(StringError::Unmappable {
+                charset,
+                index,
+                character,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn validate(bytes: &[u8], charset: Charset) -> Result<(), StringError> {
+    let malformed = |offset| Err(StringError::Malformed { charset, offset });
+    match charset {
+        Charset::Utf8 => match core::str::from_utf8(bytes) {
+            Ok(_) => Ok(()),
+            Err(e) => malformed(e.valid_up_to()),
+        },
+        Charset::Utf16Le | Charset::Utf16Be => {
+            if bytes.len() % 2 == 1 {
+                return malformed(bytes.len() - 1);
+            }
+            let mut offset = 0;
+            for character in core::char::decode_utf16(utf16_units(bytes, charset)) {
+                match character {
+                    Ok(c) => offset += c.len_utf16() * 2,
+                    Err(_) => return malformed(offset),
+                }
+            }
+            Ok(())
+        }
+        Charset::Iso8859_1 => Ok(()),
+        Charset::Ascii => match bytes.iter().position(|b| !b.is_ascii()) {
+            Some(offset) => malformed(offset),
+            None => Ok(()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polyglot::mock::MockValue;
+    use crate::polyglot::PolyglotValue;
+    use std::rc::Rc;
+
+    fn string(s: &str) -> PolyglotValue {
+        MockValue::string(s).receive()
+    }
+
+    #[test]
+    fn round_trips_every_charset() {
+        let cases: [(Charset, &str, &[u8]); 5] = [
+            (Charset::Utf8, "h\u{e9}\u{1f600}", b"h\xc3\xa9\xf0\x9f\x98\x80"),
+            (Charset::Utf16Le, "h\u{e9}", b"h\x00\xe9\x00"),
+            (Charset::Utf16Be, "h\u{e9}", b"\x00h\x00\xe9"),
+            (Charset::Iso8859_1, "h\u{e9}", b"h\xe9"),
+            (Charset::Ascii, "hi?", b"hi?"),
+        ];
+        for &(charset, text, bytes) in &cases {
+            assert_eq!(encode(&string(text), charset).unwrap(), bytes, "{}", charset);
+            let decoded: PolyglotValue = decode(bytes, charset).unwrap();
+            assert_eq!(decoded.to_rust_string().unwrap(), text, "{}", charset);
+        }
+    }
+
+    #[test]
+    fn encodes_long_strings_without_truncating() {
+        let text = "\u{1f600}\u{e9}x".repeat(1000);
+        assert_eq!(to_rust_string(&string(&text)).unwrap(), text);
+        assert_eq!(encode(&string(&text), Charset::Utf16Be).unwrap().len(), 4000 * 2);
+    }
+
+    #[test]
+    fn encode_rejects_values_that_are_not_strings() {
+        let number: PolyglotValue = MockValue::from(3).receive();
+        assert_eq!(encode(&number, Charset::Utf8), Err(StringError::NotAString));
+    }
+
+    #[test]
+    fn encode_reports_unmappable_characters() {
+        assert_eq!(
+            encode(&string("a\u{e9}\u{20ac}"), Charset::Iso8859_1),
+            Err(StringError::Unmappable {
+                charset: Charset::Iso8859_1,
+                index: 2,
+                character: '\u{20ac}',
+            })
+        );
+        assert_eq!(
+            encode(&string("\u{e9}"), Charset::Ascii),
+            Err(StringError::Unmappable {
+                charset: Charset::Ascii,
+                index: 0,
+                character: '\u{e9}',
+            })
+        );
+    }
+
+    #[test]
+    fn encode_reports_unpaired_surrogates_in_every_charset() {
+        let value: PolyglotValue = MockValue::String(Rc::new(vec![0x61, 0xd83d, 0x62])).receive();
+        let charsets = [
+            Charset::Utf8,
+            Charset::Utf16Le,
+            Charset::Utf16Be,
+            Charset::Iso8859_1,
+            Charset::Ascii,
+        ];
+        for &charset in &charsets {
+            assert_eq!(
+                encode(&value, charset),
+                Err(StringError::UnpairedSurrogate {
+                    charset,
+                    index: 1,
+                    unit: 0xd83d,
+                })
+            );
+        }
+
+        let trailing: PolyglotValue = MockValue::String(Rc::new(vec![0x7a, 0xde00])).receive();
+        assert_eq!(
+            to_rust_string(&trailing),
+            Err(StringError::UnpairedSurrogate {
+                charset: Charset::Utf8,
+                index: 1,
+                unit: 0xde00,
+            })
+        );
+    }
+
+    #[test]
+    fn decode_reports_malformed_input() {
+        let malformed = |charset, offset| Err(StringError::Malformed { charset, offset });
+        assert_eq!(
+            decode::<PolyglotValue>(b"ab\xff", Charset::Utf8).map(|_| ()),
+            malformed(Charset::Utf8, 2)
+        );
+        assert_eq!(
+            decode::<PolyglotValue>(b"a\x00b", Charset::Utf16Le).map(|_| ()),
+            malformed(Charset::Utf16Le, 2)
+        );
+        assert_eq!(
+            decode::<PolyglotValue>(b"a\x00\x00\xdc", Charset::Utf16Le).map(|_| ()),
+            malformed(Charset::Utf16Le, 2)
+        );
+        assert_eq!(
+            decode::<PolyglotValue>(b"ok\x80", Charset::Ascii).map(|_| ()),
+            malformed(Charset::Ascii, 2)
+        );
+    }
+
+    #[test]
+    fn decode_keeps_zero_bytes() {
+        let value: PolyglotValue = decode(b"a\x00b", Charset::Utf8).unwrap();
+        assert_eq!(value.to_rust_string().unwrap(), "a\u{0}b");
+    }
+}
@@ -0,0 +1,137 @@
+//! Runtime support for `#[graal_test]`.  `graal_test_main!` generates an entry point that calls
+//! `run`, which `lli` executes and `cargo graal test` reads the output of.
+//!
+//! Each test is reported on standard output with lines starting with `MARKER`, and anything the
+//! test prints in between is its captured output.  Panic messages are printed to standard output
+//! as well, so the runner sees them in order.
+
+use std::any::Any;
+use std::fmt::Debug;
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Starts every line of the protocol between a test binary and the runner.
+pub const MARKER: &str = "#graal-test#";
+
+/// A test collected by `#[graal_test]`.
+pub struct Test {
+    /// The path of the test function, like `tests::list_add`.
+    pub name: &'static str,
+    pub run: fn(),
+    pub ignore: bool,
+    pub should_panic: ShouldPanic,
+}
+
+pub enum ShouldPanic {
+    No,
+    Yes,
+    /// The panic message must contain this string.
+    WithMessage(&'static str),
+}
+
+/// The return type of a test function:  `()`, or a `Result` that fails the test if it's `Err`.
+pub trait TestResult {
+    fn check(self);
+}
+
+impl TestResult for () {
+    fn check(self) {}
+}
+
+impl<E: Debug> TestResult for Result<(), E> {
+    fn check(self) {
+        if let Err(e) = self {
+            panic!("test returned Err({:?})", e);
+        }
+    }
+}
+
+/// Runs the tests selected by the command line, returning the exit code.
+///
+/// `--list` lists the tests instead of running them.  Any other arguments are names of tests to
+/// run;  with none, every test runs.  `--ignored` runs only ignored tests, and `--include-ignored`
+/// runs them along with the rest.
+pub fn run(tests: Vec<Test>, args: Vec<String>) -> i32 {
+    let args = &args[args.len().min(1)..];
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+    let names: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+    let tests = tests.iter().map(|test| (strip_crate(test.name), test));
+
+    if flag("--list") {
+        for (name, _) in tests {
+            println!("{}: test", name);
+        }
+        return 0;
+    }
+
+    panic::set_hook(Box::new(|info| println!("thread 'main' {}", info)));
+    let mut failed = false;
+    for (name, test) in
+        tests.filter(|(name, _)| names.is_empty() || names.iter().any(|n| n == name))
+    {
+        let run = if flag("--ignored") {
+            test.ignore
+        } else {
+            !test.ignore || flag("--include-ignored")
+        };
+        if !run {
+            report("ignored", name);
+            continue;
+        }
+
+        report("start", name);
+        let result = panic::catch_unwind(AssertUnwindSafe(test.run));
+        let error = match (result, &test.should_panic) {
+            (Ok(()), ShouldPanic::No) => None,
+            (Ok(()), _) => Some("test did not panic as expected".to_owned()),
+            (Err(_), ShouldPanic::No) => Some(String::new()),
+            (Err(_), ShouldPanic::Yes) => None,
+            (Err(payload), ShouldPanic::WithMessage(expected)) => {
+                let message = panic_message(&*payload);
+                if message.contains(expected) {
+                    None
+                } else {
+                    Some(format!(
+                        "panic did not contain expected string\n      panic message: {:?}\n expected substring: {:?}",
+                        message, expected
+                    ))
+                }
+            }
+        };
+        match error {
+            None => report("ok", name),
+            Some(error) => {
+                if !error.is_empty() {
+                    println!("note: {}", error);
+                }
+                report("failed", name);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        101
+    } else {
+        0
+    }
+}
+
+fn report(status: &str, name: &str) {
+    println!("{} {} {}", MARKER, status, name);
+    let _ = std::io::stdout().flush();
+}
+
+/// `module_path!` starts with the crate name, which libtest leaves out of test names.
+fn strip_crate(name: &str) -> &str {
+    name.split_once("::").map_or(name, |(_, rest)| rest)
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        ""
+    }
+}
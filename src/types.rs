@@ -1,3 +1,6 @@
+use core::convert::TryFrom;
+use core::fmt;
+
 pub mod ctypes {
     use crate::polyglot::Value;
 
@@ -17,7 +20,9 @@ pub mod ctypes {
     pub type c_double = f64;
 }
 
+/// Java's primitive types.  `char` is `jchar`, so it doesn't shadow Rust's `char`.
 pub mod jtypes {
+    pub type jchar = super::JavaChar;
     pub type byte = i8;
     pub type short = i16;
     pub type int = i32;
@@ -26,3 +31,135 @@ pub mod jtypes {
     pub type double = f64;
     pub type boolean = bool;
 }
+
+/// A Java `char`: a single UTF-16 code unit, which may be half of a surrogate pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JavaChar(pub u16);
+
+impl JavaChar {
+    pub fn is_surrogate(self) -> bool {
+        (0xd800..=0xdfff).contains(&self.0)
+    }
+
+    pub fn is_high_surrogate(self) -> bool {
+        (0xd800..=0xdbff).contains(&self.0)
+    }
+
+    pub fn is_low_surrogate(self) -> bool {
+        (0xdc00..=0xdfff).contains(&self.0)
+    }
+
+    /// Splits a Rust `char` into one Java `char`, or a high and low surrogate if it's outside the
+    /// Basic Multilingual Plane.
+    pub fn encode(c: char) -> (JavaChar, Option<JavaChar>) {
+        let mut units = [0; 2];
+        match *c.encode_utf16(&mut units) {
+            [unit] => (JavaChar(unit), None),
+            [high, low] => (JavaChar(high), Some(JavaChar(low))),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Combines a surrogate pair into a Rust `char`.  Returns `None` if `high` and `low` aren't a
+    /// high and a low surrogate.
+    pub fn decode_pair(high: JavaChar, low: JavaChar) -> Option<char> {
+        if !high.is_high_surrogate() || !low.is_low_surrogate() {
+            return None;
+        }
+        core::char::decode_utf16([high.0, low.0].iter().cloned())
+            .next()
+            .and_then(Result::ok)
+    }
+}
+
+impl From<u16> for JavaChar {
+    fn from(unit: u16) -> Self {
+        JavaChar(unit)
+    }
+}
+
+impl From<JavaChar> for u16 {
+    fn from(c: JavaChar) -> Self {
+        c.0
+    }
+}
+
+impl TryFrom<char> for JavaChar {
+    type Error = CharConversionError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match JavaChar::encode(c) {
+            (unit, None) => Ok(unit),
+            (_, Some(_)) => Err(CharConversionError::Supplementary(c)),
+        }
+    }
+}
+
+impl TryFrom<JavaChar> for char {
+    type Error = CharConversionError;
+
+    fn try_from(c: JavaChar) -> Result<Self, Self::Error> {
+        core::char::from_u32(c.0 as u32).ok_or(CharConversionError::UnpairedSurrogate(c))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CharConversionError {
+    /// The Rust `char` needs a surrogate pair, so it doesn't fit in one Java `char`.
+    Supplementary(char),
+    /// The Java `char` is half of a surrogate pair, so it isn't a Rust `char` on its own.
+    UnpairedSurrogate(JavaChar),
+}
+
+impl fmt::Display for CharConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CharConversionError::Supplementary(c) => write!(
+                f,
+                "U+{:04X} is outside the Basic Multilingual Plane and needs two Java chars",
+                *c as u32
+            ),
+            CharConversionError::UnpairedSurrogate(c) => {
+                write!(f, "U+{:04X} is an unpaired surrogate", c.0)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CharConversionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_surrogates() {
+        assert!(!JavaChar(0x41).is_surrogate());
+        assert!(JavaChar(0xd83d).is_high_surrogate());
+        assert!(JavaChar(0xde00).is_low_surrogate());
+        assert!(!JavaChar(0xde00).is_high_surrogate());
+    }
+
+    #[test]
+    fn encodes_and_decodes_surrogate_pairs() {
+        assert_eq!(JavaChar::encode('\u{e9}'), (JavaChar(0xe9), None));
+        let (high, low) = JavaChar::encode('\u{1f600}');
+        assert_eq!((high, low), (JavaChar(0xd83d), Some(JavaChar(0xde00))));
+        assert_eq!(JavaChar::decode_pair(high, low.unwrap()), Some('\u{1f600}'));
+        assert_eq!(JavaChar::decode_pair(low.unwrap(), high), None);
+    }
+
+    #[test]
+    fn converts_to_and_from_rust_chars() {
+        assert_eq!(JavaChar::try_from('x'), Ok(JavaChar(0x78)));
+        assert_eq!(
+            JavaChar::try_from('\u{1f600}'),
+            Err(CharConversionError::Supplementary('\u{1f600}'))
+        );
+        assert_eq!(char::try_from(JavaChar(0x78)), Ok('x'));
+        assert_eq!(
+            char::try_from(JavaChar(0xd83d)),
+            Err(CharConversionError::UnpairedSurrogate(JavaChar(0xd83d)))
+        );
+    }
+}